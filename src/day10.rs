@@ -1,5 +1,13 @@
+use crate::grid::{Coordinate, Grid};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(u8)]
@@ -14,26 +22,35 @@ enum TileType {
     Start = b'S',
 }
 
-impl<T> From<T> for TileType
-where
-    T: Into<char>,
-{
-    fn from(c: T) -> Self {
-        match c.into() {
-            '.' => TileType::Empty,
-            '|' => TileType::NorthSouth,
-            '-' => TileType::EastWest,
-            'L' => TileType::NorthEast,
-            'J' => TileType::NorthWest,
-            'F' => TileType::SouthEast,
-            '7' => TileType::SouthWest,
-            'S' => TileType::Start,
-            _ => unreachable!(),
+impl TryFrom<char> for TileType {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(TileType::Empty),
+            '|' => Ok(TileType::NorthSouth),
+            '-' => Ok(TileType::EastWest),
+            'L' => Ok(TileType::NorthEast),
+            'J' => Ok(TileType::NorthWest),
+            'F' => Ok(TileType::SouthEast),
+            '7' => Ok(TileType::SouthWest),
+            'S' => Ok(TileType::Start),
+            _ => Err(format!("Unknown tile '{}'.", c)),
         }
     }
 }
 
 impl TileType {
+    /// The tile types that are pieces of pipe.
+    const PIPES: [TileType; 6] = [
+        TileType::NorthSouth,
+        TileType::EastWest,
+        TileType::NorthEast,
+        TileType::NorthWest,
+        TileType::SouthEast,
+        TileType::SouthWest,
+    ];
+
     fn neighbouring_directions(&self) -> Vec<Direction> {
         match self {
             TileType::Empty => vec![],
@@ -43,12 +60,8 @@ impl TileType {
             TileType::NorthWest => vec![Direction::North, Direction::West],
             TileType::SouthEast => vec![Direction::South, Direction::East],
             TileType::SouthWest => vec![Direction::South, Direction::West],
-            TileType::Start => vec![
-                Direction::North,
-                Direction::East,
-                Direction::South,
-                Direction::West,
-            ],
+            // The pipe under the start tile is unknown until it is inferred from its neighbors.
+            TileType::Start => vec![],
         }
     }
 
@@ -66,114 +79,173 @@ impl TileType {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct Coordinate {
-    x: usize,
-    y: usize,
+#[derive(Debug, PartialEq)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
 }
 
-impl Coordinate {
-    fn try_from_xy(x: isize, y: isize) -> Option<Self> {
-        if x < 0 || y < 0 {
-            return None;
+impl Direction {
+    fn get_offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
         }
-        Some(Coordinate {
-            x: x as usize,
-            y: y as usize,
-        })
     }
+}
+
+/// The size in pixels of a tile in a rendered image.
+const CELL_SIZE: usize = 5;
+
+/// The color of the pipe of the loop in a rendered image.
+const PIPE_COLOR: Color = [230, 230, 230];
+
+type Color = [u8; 3];
 
-    fn neighbor(&self, d: &Direction) -> Option<Coordinate> {
-        match d {
-            Direction::North => Coordinate::try_from_xy(self.x as isize, self.y as isize - 1),
-            Direction::East => Coordinate::try_from_xy(self.x as isize + 1, self.y as isize),
-            Direction::South => Coordinate::try_from_xy(self.x as isize, self.y as isize + 1),
-            Direction::West => Coordinate::try_from_xy(self.x as isize - 1, self.y as isize),
+/// How a tile relates to the loop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TileClass {
+    Start,
+    Loop,
+    Inside,
+    Outside,
+}
+
+impl TileClass {
+    fn get_color(&self) -> Color {
+        match self {
+            TileClass::Start => [40, 180, 70],
+            TileClass::Loop => [30, 30, 30],
+            TileClass::Inside => [240, 190, 40],
+            TileClass::Outside => [70, 70, 90],
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ImageFormat {
+    Ppm,
+    Svg,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "svg" => Ok(ImageFormat::Svg),
+            _ => Err(format!("Unknown image format: {}", s)),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 struct Tiles {
-    tiles: HashMap<Coordinate, (TileType, Option<usize>)>,
-    size: (usize, usize),
-}
-
-impl<T> From<T> for Tiles
-where
-    T: Into<String>,
-{
-    fn from(input: T) -> Self {
-        let mut tiles = HashMap::new();
-        let mut x = 0;
-        let mut y = 0;
-        for line in input.into().lines() {
-            x = 0;
-            for c in line.chars() {
-                tiles.insert(Coordinate { x, y }, (TileType::from(c), None));
-                x += 1;
-            }
-            y += 1;
-        }
-        Tiles {
-            tiles,
-            size: (x, y),
-        }
+    tiles: Grid<(TileType, Option<usize>)>,
+    start: Coordinate,
+}
+
+impl TryFrom<&str> for Tiles {
+    type Error = String;
+
+    /// Parses the tiles and replaces the start tile with the pipe that is under it.
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let rows = input
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| Ok((TileType::try_from(c)?, None)))
+                    .collect::<Result<Vec<_>, String>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let tiles = Grid::from_rows(rows);
+        let start = tiles
+            .iter()
+            .find(|(_, (tile_type, _))| *tile_type == TileType::Start)
+            .map(|(c, _)| c)
+            .ok_or("There is no start tile.")?;
+        let mut tiles = Tiles { tiles, start };
+        tiles.tiles[start].0 = tiles.infer_start_tile_type()?;
+        Ok(tiles)
     }
 }
 
 impl Tiles {
     fn get_start_coordinate(&self) -> Coordinate {
-        for (coordinate, (tile_type, _)) in self.tiles.iter() {
-            if *tile_type == TileType::Start {
-                return coordinate.clone();
-            }
+        self.start
+    }
+
+    /// Returns the pipe that connects the start tile to the two neighboring pipes that lead to it.
+    fn infer_start_tile_type(&self) -> Result<TileType, String> {
+        let connected: Vec<Coordinate> = self
+            .tiles
+            .neighbors4(self.start)
+            .filter(|n| self.get_pipe_ends(*n).contains(&self.start))
+            .collect();
+        if connected.len() != 2 {
+            return Err(format!(
+                "The start tile connects to {} pipes instead of 2.",
+                connected.len()
+            ));
         }
-        unreachable!();
+        let tile_type = TileType::PIPES
+            .into_iter()
+            .find(|tile_type| {
+                tile_type.neighbouring_directions().iter().all(|direction| {
+                    let neighbor = self.tiles.neighbor(self.start, direction.get_offset());
+                    neighbor.is_some_and(|n| connected.contains(&n))
+                })
+            })
+            .unwrap();
+        Ok(tile_type)
     }
 
-    fn get_neighbors(&self, c: &Coordinate) -> Vec<Coordinate> {
-        let mut neighbors = vec![];
-        let (tile_type, _) = self.tiles.get(c).unwrap();
-        for direction in tile_type.neighbouring_directions() {
-            let neighbor = c.neighbor(&direction);
-            if let Some(n) = neighbor {
-                let (neighbor_tile_type, _) = self.tiles.get(&n).unwrap();
-                // If we are on a start tile we need to check if the tile we are moving to is
-                // connected to us.
-                if *tile_type == TileType::Start {
-                    let n_directions = neighbor_tile_type.neighbouring_directions();
-                    let is_connected = match direction {
-                        Direction::North => n_directions.contains(&Direction::South),
-                        Direction::East => n_directions.contains(&Direction::West),
-                        Direction::South => n_directions.contains(&Direction::North),
-                        Direction::West => n_directions.contains(&Direction::East),
-                    };
-                    if is_connected {
-                        neighbors.push(n);
-                    }
-                } else {
-                    // We are not on a start tile, we can assume we are in a closed loop and the
-                    // next neighbor links back to us.
-                    neighbors.push(n);
+    /// Returns the coordinates the pipe on the given tile leads to.
+    fn get_pipe_ends(&self, c: Coordinate) -> Vec<Coordinate> {
+        let (tile_type, _) = self.tiles[c];
+        tile_type
+            .neighbouring_directions()
+            .iter()
+            .filter_map(|direction| self.tiles.neighbor(c, direction.get_offset()))
+            .collect()
+    }
+
+    /// Returns the neighbors that are connected to the given tile by pipes leading both ways.
+    fn get_connected_neighbors(&self, c: Coordinate) -> Vec<Coordinate> {
+        self.get_pipe_ends(c)
+            .into_iter()
+            .filter(|n| self.get_pipe_ends(*n).contains(&c))
+            .collect()
+    }
+
+    /// Returns the shortest distance along the pipes from the start to each tile, by doing a
+    /// breadth-first search in both directions at once. Tiles that can't be reached have no
+    /// distance.
+    fn get_distance_map(&self) -> Grid<Option<usize>> {
+        let mut distances =
+            Grid::from_rows(vec![vec![None; self.tiles.width()]; self.tiles.height()]);
+        distances[self.start] = Some(0);
+        let mut queue = VecDeque::from([(self.start, 0)]);
+        while let Some((c, distance)) = queue.pop_front() {
+            for neighbor in self.get_connected_neighbors(c) {
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = Some(distance + 1);
+                    queue.push_back((neighbor, distance + 1));
                 }
             }
         }
-        neighbors
+        distances
     }
 
     fn next(&self, curpos: &Coordinate) -> Option<Coordinate> {
-        let neighbors = self.get_neighbors(curpos);
+        let neighbors = self.get_pipe_ends(*curpos);
         for neighbor in neighbors {
-            let (_, distance) = self.tiles.get(&neighbor).unwrap();
+            let (_, distance) = self.tiles[neighbor];
             // Only return the neighbor if we didn't visit it before.
             if distance.is_none() {
                 return Some(neighbor);
@@ -182,19 +254,21 @@ impl Tiles {
         None
     }
 
-    fn walk(&mut self) -> usize {
-        let mut distance = 0;
+    /// Walks the pipe from the start tile until it loops back, marking the distance to each visited
+    /// tile. Returns the visited tiles in walking order, which are the vertices of the loop.
+    fn walk(&mut self) -> Vec<Coordinate> {
+        let mut path = vec![];
 
         // Start walking at the start tile.
         let mut curpos = self.get_start_coordinate();
 
         loop {
             // Mark the current tile as visited.
-            let (_, d) = self.tiles.get_mut(&curpos).unwrap();
-            *d = Some(distance);
+            let (_, d) = &mut self.tiles[curpos];
+            *d = Some(path.len());
+            path.push(curpos);
 
             // Walk to the next tile.
-            distance += 1;
             if let Some(nextpos) = self.next(&curpos) {
                 curpos = nextpos;
             } else {
@@ -202,104 +276,526 @@ impl Tiles {
                 break;
             }
         }
-        // Return the distance walked.
-        distance
+        path
+    }
+
+    /// Classifies the tiles by scanning each row and counting the crossings with the loop. The
+    /// tiles should be walked first.
+    fn get_tile_classes(&self) -> Grid<TileClass> {
+        use TileType::*;
+        let mut rows = vec![];
+        for y in 0..self.tiles.height() {
+            let mut row = vec![];
+            let mut found_vertical_pipes = 0;
+            let mut prev = Empty;
+            for x in 0..self.tiles.width() {
+                let coord = Coordinate { x, y };
+                let mut cur = self.tiles[coord].0;
+                // If the current tile is a piece of junk pipe, consider it empty.
+                if self.is_empty(&coord) {
+                    cur = Empty;
+                }
+                match cur {
+                    // If the tile is a vertical pipe, we need to track how many we have seen so
+                    // far.
+                    NorthSouth => found_vertical_pipes += 1,
+                    // If the tile is a horizontal pipe, we ignore it.
+                    EastWest => {
+                        cur = prev;
+                    }
+                    // If the tile is a corner pipe, we need to check if it continues the direction
+                    // of the previous tile. Then it counts as a vertical pipe.
+                    NorthWest if prev == SouthEast => found_vertical_pipes += 1,
+                    SouthWest if prev == NorthEast => found_vertical_pipes += 1,
+                    _ => {}
+                }
+                // Empty tiles are inside if there are an odd number of vertical pipes to the left
+                // (or right).
+                row.push(match cur {
+                    _ if coord == self.start => TileClass::Start,
+                    Empty if found_vertical_pipes % 2 == 1 => TileClass::Inside,
+                    Empty => TileClass::Outside,
+                    _ => TileClass::Loop,
+                });
+                prev = cur;
+            }
+            rows.push(row);
+        }
+        Grid::from_rows(rows)
+    }
+
+    /// Returns the color of the pipe on the given tile of a loop with the given length. In a heat
+    /// map the color goes from blue to red as the distance from the start increases. The walk goes
+    /// around the loop in one direction, so the distance is the shortest way around.
+    fn get_pipe_color(&self, c: Coordinate, heat_map: bool, loop_length: usize) -> Color {
+        match (heat_map, self.tiles[c].1) {
+            (true, Some(i)) => {
+                let distance = i.min(loop_length - i);
+                let heat = (distance * 255 / (loop_length / 2).max(1)) as u8;
+                [heat, 64, 255 - heat]
+            }
+            _ => PIPE_COLOR,
+        }
+    }
+
+    /// Renders the walked tiles as a binary PPM image. Each tile is drawn as a square cell colored
+    /// by its class, with the pipe of the loop drawn on top.
+    fn to_ppm(&self, heat_map: bool) -> Vec<u8> {
+        let classes = self.get_tile_classes();
+        let loop_length = self.tiles.iter().filter(|(_, (_, d))| d.is_some()).count();
+        let center = CELL_SIZE / 2;
+        let mut rows = vec![];
+        for y in 0..self.tiles.height() * CELL_SIZE {
+            let mut row = vec![];
+            for x in 0..self.tiles.width() * CELL_SIZE {
+                let c = Coordinate {
+                    x: x / CELL_SIZE,
+                    y: y / CELL_SIZE,
+                };
+                let (px, py) = (x % CELL_SIZE, y % CELL_SIZE);
+                let class = classes[c];
+                // Draw the pipe from the center of the cell towards the connected sides.
+                let on_pipe = matches!(class, TileClass::Loop | TileClass::Start)
+                    && self.tiles[c]
+                        .0
+                        .neighbouring_directions()
+                        .iter()
+                        .any(|direction| match direction {
+                            Direction::North => px == center && py <= center,
+                            Direction::East => py == center && px >= center,
+                            Direction::South => px == center && py >= center,
+                            Direction::West => py == center && px <= center,
+                        });
+                row.push(match on_pipe {
+                    true => self.get_pipe_color(c, heat_map, loop_length),
+                    false => class.get_color(),
+                });
+            }
+            rows.push(row);
+        }
+        let pixels = Grid::from_rows(rows);
+
+        let mut ppm = format!("P6\n{} {}\n255\n", pixels.width(), pixels.height()).into_bytes();
+        ppm.extend(pixels.rows().flatten().flatten());
+        ppm
+    }
+
+    /// Renders the walked tiles as an SVG image, using one unit per tile.
+    fn to_svg(&self, heat_map: bool) -> String {
+        let classes = self.get_tile_classes();
+        let loop_length = self.tiles.iter().filter(|(_, (_, d))| d.is_some()).count();
+        let hex = |[r, g, b]: Color| format!("#{:02x}{:02x}{:02x}", r, g, b);
+        let (width, height) = (self.tiles.width(), self.tiles.height());
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            width * CELL_SIZE,
+            height * CELL_SIZE,
+            width,
+            height
+        );
+        for (c, class) in classes.iter() {
+            svg += &format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>\n",
+                c.x,
+                c.y,
+                hex(class.get_color())
+            );
+        }
+        for (c, class) in classes.iter() {
+            if !matches!(class, TileClass::Loop | TileClass::Start) {
+                continue;
+            }
+            let (cx, cy) = (c.x as f32 + 0.5, c.y as f32 + 0.5);
+            let path: String = self.tiles[c]
+                .0
+                .neighbouring_directions()
+                .iter()
+                .map(|direction| {
+                    let (dx, dy) = direction.get_offset();
+                    format!(
+                        "M{} {}L{} {}",
+                        cx,
+                        cy,
+                        cx + dx as f32 / 2.0,
+                        cy + dy as f32 / 2.0
+                    )
+                })
+                .collect();
+            svg += &format!(
+                "  <path d=\"{}\" stroke=\"{}\" stroke-width=\"0.2\"/>\n",
+                path,
+                hex(self.get_pipe_color(c, heat_map, loop_length))
+            );
+        }
+        svg + "</svg>\n"
     }
 
     /// Returns whether the tile at the given coordinate is empty or not. A tile is empty if it is
     /// an empty tile or if it is unvisited after walking the entire pipe.
     fn is_empty(&self, c: &Coordinate) -> bool {
-        let (tile_type, distance) = self.tiles.get(c).unwrap();
-        *tile_type == TileType::Empty || distance.is_none()
+        let (tile_type, distance) = self.tiles[*c];
+        tile_type == TileType::Empty || distance.is_none()
     }
 }
 
 // A display formatter for tiles.
 impl std::fmt::Display for Tiles {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut pipe = String::new();
-        let mut clean = String::new();
-        for y in 0..self.size.1 {
-            for x in 0..self.size.0 {
-                let (tile_type, distance) = self.tiles.get(&Coordinate { x, y }).unwrap();
-                let c = tile_type.get_box_drawing_character();
-                pipe.push(c);
-                if distance.is_some() {
-                    clean.push(c);
-                } else {
-                    clean.push('.');
-                }
-            }
-            pipe.push('\n');
-            clean.push('\n');
-        }
+        let pipe = self
+            .tiles
+            .render(|(tile_type, _)| tile_type.get_box_drawing_character());
+        let clean = self.tiles.render(|(tile_type, distance)| match distance {
+            Some(_) => tile_type.get_box_drawing_character(),
+            None => '.',
+        });
         write!(f, "{}\n{}", pipe, clean)
     }
 }
 
 #[aoc_generator(day10)]
-fn parse_input(input: &str) -> Tiles {
-    input.into()
+fn parse_input(input: &str) -> Result<Tiles, String> {
+    Tiles::try_from(input)
+}
+
+/// Returns the largest distance in the distance map, and the tiles at that distance.
+fn get_farthest_tiles(distances: &Grid<Option<usize>>) -> (usize, Vec<Coordinate>) {
+    let max_distance = distances.iter().filter_map(|(_, d)| *d).max().unwrap_or(0);
+    let farthest = distances
+        .iter()
+        .filter(|(_, d)| **d == Some(max_distance))
+        .map(|(c, _)| c)
+        .collect();
+    (max_distance, farthest)
 }
 
 #[aoc(day10, part1)]
 fn part1(tiles: &Tiles) -> usize {
-    let mut tiles: Tiles = tiles.clone();
-    tiles.walk() / 2
+    let (max_distance, _) = get_farthest_tiles(&tiles.get_distance_map());
+    max_distance
 }
 
-#[aoc(day10, part2)]
-fn part2(tiles: &Tiles) -> usize {
-    use TileType::*;
+/// Outputs the answer followed by the coordinates of the farthest tiles.
+#[aoc(day10, part1, Farthest)]
+fn part1_farthest(tiles: &Tiles) -> String {
+    let (max_distance, farthest) = get_farthest_tiles(&tiles.get_distance_map());
+    let coordinates: Vec<String> = farthest
+        .iter()
+        .map(|c| format!("({}, {})", c.x, c.y))
+        .collect();
+    format!(
+        "{}\nFarthest tiles: {}",
+        max_distance,
+        coordinates.join(", ")
+    )
+}
+
+/// The ways to count the tiles that are enclosed by the loop.
+#[derive(Clone, Copy, Debug)]
+enum AreaMethod {
+    /// Scans each row and counts the loop crossings to the left of each tile.
+    ScanLine,
+    /// Calculates the area of the loop with the shoelace formula and derives the number of
+    /// interior tiles with Pick's theorem.
+    Shoelace,
+    /// Flood-fills the outside of the loop on a map at three times the resolution.
+    FloodFill,
+}
+
+fn get_enclosed_tiles(tiles: &Tiles, method: AreaMethod) -> usize {
     // Walk the tiles so we know which tiles are part of our pipe.
     let mut tiles: Tiles = tiles.clone();
-    tiles.walk();
-
-    // Convert the tiles to a format that we can use to calculate whether a tile is inside our pipe.
-    let mut count = 0;
-    for y in 0..tiles.size.1 {
-        let mut found_vertical_pipes = 0;
-        let mut prev = Empty;
-        for x in 0..tiles.size.0 {
-            let coord = Coordinate { x, y };
-            let mut cur = tiles.tiles.get(&Coordinate { x, y }).unwrap().0;
-            // If the current tile is a piece of junk pipe, consider it empty.
-            if tiles.is_empty(&coord) {
-                cur = Empty;
-            }
-            match cur {
-                // If the tile is a vertical pipe, we need to track how many we have seen so far.
-                NorthSouth => found_vertical_pipes += 1,
-                // If the tile is a horizontal pipe, we ignore it.
-                EastWest => {
-                    cur = prev;
-                }
-                // If the tile is a corner pipe, we need to check if it continues the direction of
-                // the previous tile. Then it counts as a vertical pipe.
-                NorthWest => {
-                    if prev == SouthEast {
-                        found_vertical_pipes += 1;
-                    }
-                }
-                SouthWest => {
-                    if prev == NorthEast {
-                        found_vertical_pipes += 1;
-                    }
-                }
-                // If the tile is empty, we need to check if it is enclosed by the pipe. Tiles are
-                // inside if there are an odd number of vertical pipes to the left (or right).
-                Empty => {
-                    if found_vertical_pipes % 2 == 1 {
-                        count += 1;
-                    }
-                }
-                _ => {}
+    let path = tiles.walk();
+    match method {
+        AreaMethod::ScanLine => count_enclosed_tiles_by_scan_line(&tiles),
+        AreaMethod::Shoelace => count_enclosed_tiles_by_shoelace(&path),
+        AreaMethod::FloodFill => count_enclosed_tiles_by_flood_fill(&tiles),
+    }
+}
+
+fn count_enclosed_tiles_by_scan_line(tiles: &Tiles) -> usize {
+    tiles
+        .get_tile_classes()
+        .iter()
+        .filter(|(_, class)| **class == TileClass::Inside)
+        .count()
+}
+
+fn count_enclosed_tiles_by_shoelace(path: &[Coordinate]) -> usize {
+    // The shoelace formula gives twice the area of the polygon traced by the centers of the loop
+    // tiles.
+    let double_area = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+        .sum::<i64>()
+        .unsigned_abs() as usize;
+    // Pick's theorem: A = i + b/2 - 1, with i the number of interior points and b the number of
+    // points on the boundary, which are the tiles of the loop.
+    (double_area + 2).saturating_sub(path.len()) / 2
+}
+
+/// The cells of a map at three times the resolution of the tiles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum UpsampledCell {
+    Pipe,
+    Outside,
+    Inside,
+}
+
+/// Returns a map where each tile becomes a 3×3 block with the pipe of the loop drawn through the
+/// center. Pipes that run next to each other now have a gap between them, so the outside can be
+/// flood-filled from the border. The tiles should be walked first.
+fn get_upsampled_map(tiles: &Tiles) -> Grid<UpsampledCell> {
+    let (width, height) = (tiles.tiles.width() * 3, tiles.tiles.height() * 3);
+    let mut map = Grid::from_rows(vec![vec![UpsampledCell::Inside; width]; height]);
+    for (c, (tile_type, distance)) in tiles.tiles.iter() {
+        if distance.is_none() {
+            continue;
+        }
+        let center = Coordinate {
+            x: c.x * 3 + 1,
+            y: c.y * 3 + 1,
+        };
+        map[center] = UpsampledCell::Pipe;
+        for direction in tile_type.neighbouring_directions() {
+            let end = map.neighbor(center, direction.get_offset()).unwrap();
+            map[end] = UpsampledCell::Pipe;
+        }
+    }
+
+    // Flood-fill the outside, starting from all cells on the border.
+    let mut queue: VecDeque<Coordinate> = map
+        .iter()
+        .filter(|(c, cell)| {
+            **cell == UpsampledCell::Inside
+                && (c.x == 0 || c.y == 0 || c.x == width - 1 || c.y == height - 1)
+        })
+        .map(|(c, _)| c)
+        .collect();
+    for c in &queue {
+        map[*c] = UpsampledCell::Outside;
+    }
+    while let Some(c) = queue.pop_front() {
+        let neighbors: Vec<Coordinate> = map.neighbors4(c).collect();
+        for n in neighbors {
+            if map[n] == UpsampledCell::Inside {
+                map[n] = UpsampledCell::Outside;
+                queue.push_back(n);
             }
-            prev = cur;
         }
     }
+    map
+}
+
+fn count_enclosed_tiles_by_flood_fill(tiles: &Tiles) -> usize {
+    // Down-sample by looking at the center of each tile.
+    let map = get_upsampled_map(tiles);
+    tiles
+        .tiles
+        .iter()
+        .filter(|(c, _)| {
+            let center = Coordinate {
+                x: c.x * 3 + 1,
+                y: c.y * 3 + 1,
+            };
+            map[center] == UpsampledCell::Inside
+        })
+        .count()
+}
+
+#[aoc(day10, part2)]
+fn part2(tiles: &Tiles) -> usize {
+    get_enclosed_tiles(tiles, AreaMethod::ScanLine)
+}
+
+/// Writes an image of the maze to the directory in the `DAY10_IMAGE_DIR` environment variable, if
+/// it is set. The format is set with `DAY10_IMAGE_FORMAT` (`ppm` or `svg`), and the pipe is colored
+/// by its distance from the start if `DAY10_HEAT_MAP` is set.
+#[aoc(day10, part2, Image)]
+fn part2_image(tiles: &Tiles) -> Result<usize, String> {
+    let Ok(dir) = env::var("DAY10_IMAGE_DIR") else {
+        return Ok(part2(tiles));
+    };
+    let format = match env::var("DAY10_IMAGE_FORMAT") {
+        Ok(format) => format.parse()?,
+        Err(_) => ImageFormat::Ppm,
+    };
+    let heat_map = env::var("DAY10_HEAT_MAP").is_ok();
+
+    let mut walked_tiles = tiles.clone();
+    walked_tiles.walk();
+    let (filename, image) = match format {
+        ImageFormat::Ppm => ("day10.ppm", walked_tiles.to_ppm(heat_map)),
+        ImageFormat::Svg => ("day10.svg", walked_tiles.to_svg(heat_map).into_bytes()),
+    };
+    let path = Path::new(&dir).join(filename);
+    fs::write(&path, image).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+
+    Ok(part2(tiles))
+}
+
+/// The look of a tile in a frame of the animation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FrameStyle {
+    Unvisited,
+    Visited,
+    Head,
+    Inside,
+    Outside,
+}
+
+impl FrameStyle {
+    fn get_ansi_code(&self) -> &str {
+        match self {
+            FrameStyle::Unvisited => "2",
+            FrameStyle::Visited => "32",
+            FrameStyle::Head => "1;33",
+            FrameStyle::Inside => "30;43",
+            FrameStyle::Outside => "34",
+        }
+    }
+}
+
+/// Renders a frame of the animation. Colored frames draw all tiles with box drawing characters and
+/// style them with ANSI escape codes. Plain frames use box drawing characters only for the visited
+/// pipe, and mark the head of the walk with `@` and the classified tiles with `I` and `O`.
+fn render_frame(tiles: &Tiles, styles: &Grid<FrameStyle>, colored: bool) -> String {
+    let mut frame = String::new();
+    for (c, style) in styles.iter() {
+        let (tile_type, _) = tiles.tiles[c];
+        if colored {
+            let character = tile_type.get_box_drawing_character();
+            frame += &format!("\x1b[{}m{}\x1b[0m", style.get_ansi_code(), character);
+        } else {
+            frame.push(match style {
+                FrameStyle::Unvisited => tile_type as u8 as char,
+                FrameStyle::Visited => tile_type.get_box_drawing_character(),
+                FrameStyle::Head => '@',
+                FrameStyle::Inside => 'I',
+                FrameStyle::Outside => 'O',
+            });
+        }
+        if c.x == styles.width() - 1 {
+            frame.push('\n');
+        }
+    }
+    frame
+}
+
+/// Returns the frames of an animation that walks the loop, advancing the given number of steps per
+/// frame, and then sweeps over the rows to reveal which tiles are inside or outside the loop.
+fn get_animation_frames(tiles: &Tiles, steps_per_frame: usize, colored: bool) -> Vec<String> {
+    let mut tiles = tiles.clone();
+    let path = tiles.walk();
+    let classes = tiles.get_tile_classes();
+    let (width, height) = (tiles.tiles.width(), tiles.tiles.height());
+    let mut styles = Grid::from_rows(vec![vec![FrameStyle::Unvisited; width]; height]);
+    let mut frames = vec![];
+
+    for (i, c) in path.iter().enumerate() {
+        if i > 0 {
+            styles[path[i - 1]] = FrameStyle::Visited;
+        }
+        styles[*c] = FrameStyle::Head;
+        if (i + 1) % steps_per_frame.max(1) == 0 || i == path.len() - 1 {
+            frames.push(render_frame(&tiles, &styles, colored));
+        }
+    }
+    if let Some(last) = path.last() {
+        styles[*last] = FrameStyle::Visited;
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let c = Coordinate { x, y };
+            styles[c] = match classes[c] {
+                TileClass::Inside => FrameStyle::Inside,
+                TileClass::Outside => FrameStyle::Outside,
+                TileClass::Start | TileClass::Loop => FrameStyle::Visited,
+            };
+        }
+        frames.push(render_frame(&tiles, &styles, colored));
+    }
+    frames
+}
+
+/// Plays the frames in the terminal, clearing the screen before each frame.
+fn play_animation(frames: &[String], delay: Duration) {
+    let mut stdout = io::stdout();
+    for frame in frames {
+        print!("\x1b[H\x1b[2J{}", frame);
+        stdout.flush().unwrap();
+        thread::sleep(delay);
+    }
+}
+
+/// Writes each frame to a numbered text file in the given directory.
+fn dump_frames(frames: &[String], dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    for (i, frame) in frames.iter().enumerate() {
+        let path = dir.join(format!("day10_frame_{:05}.txt", i));
+        fs::write(&path, frame)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Animates the walk and the classification of the tiles. The animation is played in the terminal
+/// if `DAY10_ANIMATE` is set, waiting `DAY10_ANIMATION_DELAY` milliseconds between frames (default
+/// 20) and advancing `DAY10_ANIMATION_STEPS` tiles per frame (default 1). Plain frames are written
+/// to the directory in `DAY10_FRAMES_DIR` if it is set.
+#[aoc(day10, part2, Animation)]
+fn part2_animation(tiles: &Tiles) -> Result<usize, String> {
+    let get_number = |var: &str, default: usize| match env::var(var) {
+        Ok(value) => value
+            .parse::<usize>()
+            .map_err(|e| format!("{}: {}", var, e)),
+        Err(_) => Ok(default),
+    };
+    let steps_per_frame = get_number("DAY10_ANIMATION_STEPS", 1)?;
+
+    if env::var("DAY10_ANIMATE").is_ok() {
+        let delay = Duration::from_millis(get_number("DAY10_ANIMATION_DELAY", 20)? as u64);
+        play_animation(&get_animation_frames(tiles, steps_per_frame, true), delay);
+    }
+    if let Ok(dir) = env::var("DAY10_FRAMES_DIR") {
+        let frames = get_animation_frames(tiles, steps_per_frame, false);
+        dump_frames(&frames, Path::new(&dir))?;
+    }
+
+    Ok(part2(tiles))
+}
+
+/// Outputs the answer found by flood-filling. The upsampled map is included if the
+/// `DAY10_SHOW_UPSAMPLED` environment variable is set.
+#[aoc(day10, part2, FloodFill)]
+fn part2_flood_fill(tiles: &Tiles) -> String {
+    let count = get_enclosed_tiles(tiles, AreaMethod::FloodFill);
+    if env::var("DAY10_SHOW_UPSAMPLED").is_err() {
+        return count.to_string();
+    }
+    let mut walked_tiles = tiles.clone();
+    walked_tiles.walk();
+    format!(
+        "{}\n{}",
+        count,
+        render_upsampled_map(&get_upsampled_map(&walked_tiles))
+    )
+}
+
+fn render_upsampled_map(map: &Grid<UpsampledCell>) -> String {
+    map.render(|cell| match cell {
+        UpsampledCell::Pipe => '#',
+        UpsampledCell::Outside => ' ',
+        UpsampledCell::Inside => 'I',
+    })
+}
 
-    count
+#[aoc(day10, part2, Shoelace)]
+fn part2_shoelace(tiles: &Tiles) -> usize {
+    get_enclosed_tiles(tiles, AreaMethod::Shoelace)
 }
 
 #[cfg(test)]
@@ -308,12 +804,12 @@ mod tests {
     use indoc::indoc;
 
     #[test]
-    fn test_get_neighbors() {
-        let tiles = parse_input(get_test_input_part1_example_1());
+    fn test_get_pipe_ends() {
+        let tiles = parse_input(get_test_input_part1_example_1()).unwrap();
         let test_cases = vec![
             (
                 Coordinate { x: 1, y: 1 },
-                vec![Coordinate { x: 2, y: 1 }, Coordinate { x: 1, y: 2 }],
+                vec![Coordinate { x: 1, y: 2 }, Coordinate { x: 2, y: 1 }],
             ),
             (
                 Coordinate { x: 2, y: 1 },
@@ -326,19 +822,19 @@ mod tests {
         ];
         // Test example 1.
         for (coordinate, expected) in &test_cases {
-            assert_eq!(*expected, tiles.get_neighbors(coordinate));
+            assert_eq!(*expected, tiles.get_pipe_ends(*coordinate));
         }
         // Test example 2. Should have the same neighbors as example 1.
-        let tiles = parse_input(get_test_input_part1_example_2());
+        let tiles = parse_input(get_test_input_part1_example_2()).unwrap();
         for (coordinate, expected) in &test_cases {
-            assert_eq!(*expected, tiles.get_neighbors(coordinate));
+            assert_eq!(*expected, tiles.get_pipe_ends(*coordinate));
         }
 
         // Test example 3.
         let test_cases = vec![
             (
                 Coordinate { x: 0, y: 2 },
-                vec![Coordinate { x: 1, y: 2 }, Coordinate { x: 0, y: 3 }],
+                vec![Coordinate { x: 0, y: 3 }, Coordinate { x: 1, y: 2 }],
             ),
             (
                 Coordinate { x: 0, y: 4 },
@@ -354,36 +850,72 @@ mod tests {
             ),
         ];
 
-        let tiles = parse_input(get_test_input_part1_example_3());
+        let tiles = parse_input(get_test_input_part1_example_3()).unwrap();
         for (coordinate, expected) in &test_cases {
-            assert_eq!(*expected, tiles.get_neighbors(coordinate));
+            assert_eq!(*expected, tiles.get_pipe_ends(*coordinate));
         }
     }
 
     #[test]
     fn test_parse_input_part1() {
         use TileType::*;
-        let expected = vec![
+        let expected = [
             vec![Empty, Empty, Empty, Empty, Empty],
-            vec![Empty, Start, EastWest, SouthWest, Empty],
+            vec![Empty, SouthEast, EastWest, SouthWest, Empty],
             vec![Empty, NorthSouth, Empty, NorthSouth, Empty],
             vec![Empty, NorthEast, EastWest, NorthWest, Empty],
             vec![Empty, Empty, Empty, Empty, Empty],
         ];
         let tiles = Tiles {
-            tiles: expected
-                .iter()
-                .enumerate()
-                .flat_map(|(y, row)| {
-                    row.iter()
-                        .enumerate()
-                        .map(move |(x, tile_type)| (Coordinate { x, y }, (*tile_type, None)))
-                })
-                .collect(),
-            size: (5, 5),
+            tiles: Grid::from_rows(
+                expected
+                    .iter()
+                    .map(|row| row.iter().map(|tile_type| (*tile_type, None)).collect())
+                    .collect(),
+            ),
+            start: Coordinate { x: 1, y: 1 },
+        };
+
+        assert_eq!(
+            tiles,
+            parse_input(get_test_input_part1_example_1()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_infer_start_tile_type() {
+        let start_tile_type = |input: &str| {
+            let tiles = parse_input(input).unwrap();
+            tiles.tiles[tiles.start].0
         };
+        assert_eq!(
+            TileType::SouthEast,
+            start_tile_type(get_test_input_part1_example_3())
+        );
+        assert_eq!(
+            TileType::SouthEast,
+            start_tile_type(get_test_input_part2_example_2())
+        );
+        assert_eq!(TileType::NorthSouth, start_tile_type(".|.\n.S.\n.|.\n"));
+        assert_eq!(TileType::NorthWest, start_tile_type(".|.\n-S.\n...\n"));
 
-        assert_eq!(tiles, parse_input(get_test_input_part1_example_1()));
+        assert_eq!(
+            Err("The start tile connects to 0 pipes instead of 2.".to_string()),
+            parse_input("...\n.S.\n...\n")
+        );
+        assert_eq!(
+            Err("The start tile connects to 1 pipes instead of 2.".to_string()),
+            parse_input("S-7\n")
+        );
+        assert_eq!(
+            Err("The start tile connects to 3 pipes instead of 2.".to_string()),
+            parse_input(".|.\n-S-\n...\n")
+        );
+        assert_eq!(
+            Err("There is no start tile.".to_string()),
+            parse_input("F7\nLJ\n")
+        );
+        assert_eq!(Err("Unknown tile 'X'.".to_string()), parse_input("S-X\n"));
     }
 
     #[test]
@@ -391,24 +923,303 @@ mod tests {
         test_parse_input_part1();
     }
 
+    #[test]
+    fn test_walk() {
+        let mut tiles = parse_input(get_test_input_part1_example_2()).unwrap();
+        let expected = [
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+            (3, 2),
+            (3, 1),
+            (2, 1),
+        ]
+        .map(|(x, y)| Coordinate { x, y });
+        assert_eq!(expected.to_vec(), tiles.walk());
+        assert_eq!((TileType::NorthWest, Some(4)), tiles.tiles[expected[4]]);
+        assert_eq!(
+            (TileType::SouthWest, None),
+            tiles.tiles[Coordinate { x: 4, y: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_area_methods() {
+        // Both methods should agree on all examples.
+        let inputs = [
+            get_test_input_part1_example_1(),
+            get_test_input_part1_example_2(),
+            get_test_input_part1_example_3(),
+            get_test_input_part2_example_1(),
+            get_test_input_part2_example_2(),
+            get_test_input_part2_example_3(),
+        ];
+        for input in inputs {
+            let tiles = parse_input(input).unwrap();
+            let expected = get_enclosed_tiles(&tiles, AreaMethod::ScanLine);
+            assert_eq!(expected, get_enclosed_tiles(&tiles, AreaMethod::Shoelace));
+            assert_eq!(expected, get_enclosed_tiles(&tiles, AreaMethod::FloodFill));
+        }
+        // The start tile is a corner, which encloses the tile to the right of it.
+        let tiles = parse_input(get_test_input_part1_example_3()).unwrap();
+        assert_eq!(1, part2(&tiles));
+    }
+
+    #[test]
+    fn test_get_animation_frames() {
+        let tiles = parse_input(get_test_input_part1_example_2()).unwrap();
+        let frames = get_animation_frames(&tiles, 1, false);
+        // One frame per step of the walk, and one per row of the sweep.
+        assert_eq!(8 + 5, frames.len());
+        let expected = indoc! {"
+            -L|F7
+            7@-7|
+            L|7||
+            -L-J|
+            L|-JF
+        "};
+        assert_eq!(expected, frames[0]);
+        let expected = indoc! {"
+            -L|F7
+            7╭-7|
+            L│7||
+            -╰─@|
+            L|-JF
+        "};
+        assert_eq!(expected, frames[4]);
+        let expected = indoc! {"
+            -L|F7
+            7╭@╮|
+            L│7│|
+            -╰─╯|
+            L|-JF
+        "};
+        assert_eq!(expected, frames[7]);
+        let expected = indoc! {"
+            OOOOO
+            O╭─╮O
+            O│I│O
+            -╰─╯|
+            L|-JF
+        "};
+        assert_eq!(expected, frames[10]);
+        let expected = indoc! {"
+            OOOOO
+            O╭─╮O
+            O│I│O
+            O╰─╯O
+            OOOOO
+        "};
+        assert_eq!(expected, frames[12]);
+
+        // Multiple steps per frame, always including the last step.
+        let frames = get_animation_frames(&tiles, 3, false);
+        assert_eq!(3 + 5, frames.len());
+        assert_eq!(frames[2], get_animation_frames(&tiles, 1, false)[7]);
+
+        let frames = get_animation_frames(&tiles, 1, true);
+        assert!(frames[0].starts_with("\x1b[2m─\x1b[0m\x1b[2m╰\x1b[0m"));
+        assert!(frames[0].contains("\x1b[1;33m╭\x1b[0m"));
+        assert_eq!(5, frames[12].lines().count());
+    }
+
+    #[test]
+    fn test_dump_frames() {
+        let dir = env::temp_dir().join(format!("day10_test_dump_frames_{}", std::process::id()));
+        let frames = vec!["ab\n".to_string(), "cd\n".to_string()];
+        dump_frames(&frames, &dir).unwrap();
+        assert_eq!(
+            "cd\n",
+            fs::read_to_string(dir.join("day10_frame_00001.txt")).unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_upsampled_map() {
+        // The pipes squeeze together in the middle of this example, leaving a gap in between them
+        // that connects to the outside.
+        let input = indoc! {"
+            ..........
+            .S------7.
+            .|F----7|.
+            .||....||.
+            .||....||.
+            .|L-7F-J|.
+            .|..||..|.
+            .L--JL--J.
+            ..........
+        "};
+        let mut tiles = parse_input(input).unwrap();
+        tiles.walk();
+        let map = get_upsampled_map(&tiles);
+        assert_eq!((30, 27), (map.width(), map.height()));
+        let rows: Vec<String> = render_upsampled_map(&map)
+            .lines()
+            .map(|line| line.to_string())
+            .collect();
+        // The gap between the pipes in the bottom middle.
+        assert_eq!("    #IIIIIIII#  #IIIIIIII#    ", rows[19]);
+        assert_eq!("    ##########  ##########    ", rows[22]);
+        assert_eq!(" ".repeat(30), rows[0]);
+        assert_eq!(4, count_enclosed_tiles_by_flood_fill(&tiles));
+    }
+
+    #[test]
+    fn test_get_tile_classes() {
+        let mut tiles = parse_input(get_test_input_part2_example_1()).unwrap();
+        tiles.walk();
+        let expected = indoc! {"
+            ...........
+            .S########.
+            .#########.
+            .##.....##.
+            .##.....##.
+            .####.####.
+            .#II#.#II#.
+            .####.####.
+            ...........
+        "};
+        let classes = tiles.get_tile_classes().render(|class| match class {
+            TileClass::Start => 'S',
+            TileClass::Loop => '#',
+            TileClass::Inside => 'I',
+            TileClass::Outside => '.',
+        });
+        assert_eq!(expected, classes);
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let mut tiles = parse_input(get_test_input_part1_example_1()).unwrap();
+        tiles.walk();
+        let ppm = tiles.to_ppm(false);
+        let header = b"P6\n25 25\n255\n";
+        assert_eq!(header, &ppm[..header.len()]);
+        assert_eq!(header.len() + 25 * 25 * 3, ppm.len());
+
+        let pixel = |ppm: &[u8], x: usize, y: usize| -> Color {
+            let i = header.len() + (y * 25 + x) * 3;
+            [ppm[i], ppm[i + 1], ppm[i + 2]]
+        };
+        // The start tile is a corner going south and east.
+        assert_eq!(TileClass::Start.get_color(), pixel(&ppm, 7, 5));
+        assert_eq!(PIPE_COLOR, pixel(&ppm, 7, 7));
+        assert_eq!(PIPE_COLOR, pixel(&ppm, 9, 7));
+        assert_eq!(PIPE_COLOR, pixel(&ppm, 7, 9));
+        assert_eq!(TileClass::Start.get_color(), pixel(&ppm, 5, 7));
+        // The tile in the middle is enclosed, the corners are outside.
+        assert_eq!(TileClass::Inside.get_color(), pixel(&ppm, 12, 12));
+        assert_eq!(TileClass::Outside.get_color(), pixel(&ppm, 0, 0));
+        assert_eq!(TileClass::Loop.get_color(), pixel(&ppm, 15, 5));
+
+        // In a heat map the start is blue and the far side of the loop is red. The tiles next to
+        // the start are equally close, whichever way the loop was walked.
+        let ppm = tiles.to_ppm(true);
+        assert_eq!([0, 64, 255], pixel(&ppm, 7, 7));
+        assert_eq!([63, 64, 192], pixel(&ppm, 12, 7));
+        assert_eq!([63, 64, 192], pixel(&ppm, 7, 12));
+        assert_eq!([255, 64, 0], pixel(&ppm, 17, 17));
+        assert_eq!(
+            PIPE_COLOR,
+            tiles.get_pipe_color(Coordinate { x: 0, y: 0 }, true, 8)
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let mut tiles = parse_input(get_test_input_part1_example_1()).unwrap();
+        tiles.walk();
+        let svg = tiles.to_svg(false);
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             width=\"25\" height=\"25\" viewBox=\"0 0 5 5\">\n"
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(25, svg.matches("<rect").count());
+        assert_eq!(8, svg.matches("<path").count());
+        assert!(svg.contains("<rect x=\"2\" y=\"2\" width=\"1\" height=\"1\" fill=\"#f0be28\"/>"));
+        assert!(svg.contains(
+            "<path d=\"M1.5 1.5L1.5 2M1.5 1.5L2 1.5\" stroke=\"#e6e6e6\" stroke-width=\"0.2\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_get_distance_map() {
+        let render = |distances: &Grid<Option<usize>>| {
+            distances.render(|d| match d {
+                Some(d) => char::from_digit(*d as u32, 10).unwrap(),
+                None => '.',
+            })
+        };
+
+        let tiles = parse_input(get_test_input_part1_example_2()).unwrap();
+        let distances = tiles.get_distance_map();
+        let expected = indoc! {"
+            .....
+            .012.
+            .1.3.
+            .234.
+            .....
+        "};
+        assert_eq!(expected, render(&distances));
+        assert_eq!(
+            (4, vec![Coordinate { x: 3, y: 3 }]),
+            get_farthest_tiles(&distances)
+        );
+
+        let tiles = parse_input(get_test_input_part1_example_3()).unwrap();
+        let distances = tiles.get_distance_map();
+        let expected = indoc! {"
+            ..45.
+            .236.
+            01.78
+            14567
+            23...
+        "};
+        assert_eq!(expected, render(&distances));
+        assert_eq!(
+            (8, vec![Coordinate { x: 4, y: 2 }]),
+            get_farthest_tiles(&distances)
+        );
+        assert_eq!("8\nFarthest tiles: (4, 2)", part1_farthest(&tiles));
+
+        // A pipe that is open at the end has two tiles at the same largest distance when its
+        // length is odd.
+        let tiles = parse_input("-S-\n").unwrap();
+        let distances = tiles.get_distance_map();
+        assert_eq!("101\n", render(&distances));
+        assert_eq!(
+            (
+                1,
+                vec![Coordinate { x: 0, y: 0 }, Coordinate { x: 2, y: 0 }]
+            ),
+            get_farthest_tiles(&distances)
+        );
+    }
+
     #[test]
     fn part1_example() {
-        let input = parse_input(get_test_input_part1_example_1());
+        let input = parse_input(get_test_input_part1_example_1()).unwrap();
         assert_eq!(4, part1(&input));
-        let input = parse_input(get_test_input_part1_example_2());
+        let input = parse_input(get_test_input_part1_example_2()).unwrap();
         assert_eq!(4, part1(&input));
-        let input = parse_input(get_test_input_part1_example_3());
+        let input = parse_input(get_test_input_part1_example_3()).unwrap();
         assert_eq!(8, part1(&input));
     }
 
     #[test]
     fn part2_example() {
-        let input = parse_input(get_test_input_part2_example_1());
+        let input = parse_input(get_test_input_part2_example_1()).unwrap();
         assert_eq!(4, part2(&input));
-        let input = parse_input(get_test_input_part2_example_2());
+        let input = parse_input(get_test_input_part2_example_2()).unwrap();
         assert_eq!(8, part2(&input));
-        let input = parse_input(get_test_input_part2_example_3());
+        let input = parse_input(get_test_input_part2_example_3()).unwrap();
         assert_eq!(10, part2(&input));
+        assert_eq!(10, part2_shoelace(&input));
+        assert_eq!("10", part2_flood_fill(&input));
     }
 
     fn get_test_input_part1_example_1<'a>() -> &'a str {
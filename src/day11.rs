@@ -1,104 +1,89 @@
+use crate::grid::Grid;
 use aoc_runner_derive::{aoc, aoc_generator};
-use nalgebra::{DMatrix, Dyn, RowVector, VecStorage, U1};
 
-#[aoc_generator(day11, part1)]
-fn parse_input_part1(input: &str) -> DMatrix<u16> {
-    // Count the number of rows and lines.
-    let mut lines = vec![];
-    let mut galaxy_count = 0;
-    for line in input.lines() {
-        let row: Vec<u16> = line
-            .chars()
-            .map(|c| match c {
-                '.' => 0,
-                '#' => {
-                    galaxy_count += 1;
-                    galaxy_count
-                }
-                _ => unreachable!("Unknown character"),
-            })
+/// A universe described by the positions of its galaxies, in reading order, and the indexes of the
+/// rows and columns that don't contain any galaxies.
+#[derive(Clone, Debug, PartialEq)]
+struct Universe {
+    galaxies: Vec<(usize, usize)>,
+    empty_rows: Vec<usize>,
+    empty_cols: Vec<usize>,
+}
+
+impl From<&Grid<bool>> for Universe {
+    fn from(image: &Grid<bool>) -> Self {
+        let galaxies = image
+            .iter()
+            .filter(|(_, is_galaxy)| **is_galaxy)
+            .map(|(c, _)| (c.x, c.y))
+            .collect();
+        let empty_rows = image
+            .rows()
+            .enumerate()
+            .filter_map(|(i, row)| row.iter().all(|g| !g).then_some(i))
             .collect();
-        lines.push(RowVector::<u16, Dyn, VecStorage<u16, U1, Dyn>>::from_vec(
-            row,
-        ));
+        let empty_cols = image
+            .columns()
+            .enumerate()
+            .filter_map(|(i, mut col)| col.all(|g| !g).then_some(i))
+            .collect();
+        Universe {
+            galaxies,
+            empty_rows,
+            empty_cols,
+        }
     }
-    DMatrix::from_rows(&lines)
 }
 
-fn expand_universe(mut universe: DMatrix<u16>) -> DMatrix<u16> {
-    // Find empty rows and columns.
-    let empty_rows: Vec<_> = universe
-        .row_iter()
-        .enumerate()
-        .filter(|(_, row)| row.sum() == 0)
-        .map(|(i, _)| i)
-        .collect();
-    let empty_cols: Vec<_> = universe
-        .column_iter()
-        .enumerate()
-        .filter(|(_, col)| col.sum() == 0)
-        .map(|(i, _)| i)
-        .collect();
-
-    // Expand the universe.
-    for i in empty_rows.iter().rev() {
-        universe = universe.insert_row(*i, 0);
-    }
-    for i in empty_cols.iter().rev() {
-        universe = universe.insert_column(*i, 0);
+impl Universe {
+    /// Returns the positions of the galaxies after every empty row and column has grown into the
+    /// given number of rows and columns.
+    fn expand(&self, growth_rate: usize) -> Vec<(usize, usize)> {
+        // Each galaxy moves by the number of empty lines before it, which can be found with a
+        // binary search since the empty lines are sorted.
+        let expand = |v: usize, empty: &[usize]| {
+            let count = empty.partition_point(|i| *i < v);
+            v - count + count * growth_rate
+        };
+        self.galaxies
+            .iter()
+            .map(|(x, y)| (expand(*x, &self.empty_cols), expand(*y, &self.empty_rows)))
+            .collect()
     }
-    universe
 }
 
-fn get_galaxies(universe: &DMatrix<u16>) -> Vec<(usize, usize)> {
-    let mut galaxies = vec![];
-    for y in 0..universe.nrows() {
-        for x in 0..universe.ncols() {
-            if universe[(y, x)] != 0 {
-                galaxies.push((x, y));
-            }
-        }
-    }
-    galaxies
+#[aoc_generator(day11, part1)]
+fn parse_input_part1(input: &str) -> Universe {
+    let image = Grid::parse(input, |c| match c {
+        '.' => false,
+        '#' => true,
+        _ => unreachable!("Unknown character"),
+    });
+    Universe::from(&image)
 }
 
-fn get_sum_of_distances(universe: &DMatrix<u16>, growth_rate: usize) -> usize {
-    let universe = universe.clone();
-    // Locate the galaxies in the universe.
-    let mut galaxies = get_galaxies(&universe);
-
-    // Find empty rows and columns.
-    let empty_rows: Vec<_> = universe
-        .row_iter()
-        .enumerate()
-        .filter(|(_, row)| row.sum() == 0)
-        .map(|(i, _)| i)
-        .collect();
-    let empty_cols: Vec<_> = universe
-        .column_iter()
-        .enumerate()
-        .filter(|(_, col)| col.sum() == 0)
-        .map(|(i, _)| i)
-        .collect();
-
-    // Expand the galaxy.
-    for i in empty_cols.iter().rev() {
-        galaxies.iter_mut().for_each(|(x, _)| {
-            if *x > *i {
-                *x += growth_rate - 1;
-            }
-        });
-    }
-    for i in empty_rows.iter().rev() {
-        galaxies.iter_mut().for_each(|(_, y)| {
-            if *y > *i {
-                *y += growth_rate - 1;
-            }
-        });
+fn get_sum_of_distances(universe: &Universe, growth_rate: usize) -> usize {
+    let galaxies = universe.expand(growth_rate);
+    let xs = galaxies.iter().map(|(x, _)| *x).collect();
+    let ys = galaxies.iter().map(|(_, y)| *y).collect();
+    get_sum_of_axis_distances(xs) + get_sum_of_axis_distances(ys)
+}
+
+/// Returns the sum of the distances between all pairs of values. Once the values are sorted, each
+/// value is at least as large as all values before it, so its distance to them is its value times
+/// their count, minus their sum.
+fn get_sum_of_axis_distances(mut values: Vec<usize>) -> usize {
+    values.sort_unstable();
+    let mut prefix_sum = 0;
+    let mut sum = 0;
+    for (i, v) in values.into_iter().enumerate() {
+        sum += v * i - prefix_sum;
+        prefix_sum += v;
     }
-    get_distances(&galaxies).iter().sum()
+    sum
 }
 
+/// Returns the distance between each pair of galaxies.
 fn get_distances(galaxies: &[(usize, usize)]) -> Vec<usize> {
     let mut distances = vec![];
     for i in 0..galaxies.len() {
@@ -113,23 +98,26 @@ fn get_distances(galaxies: &[(usize, usize)]) -> Vec<usize> {
 }
 
 #[aoc_generator(day11, part2)]
-fn parse_input_part2(input: &str) -> DMatrix<u16> {
+fn parse_input_part2(input: &str) -> Universe {
     parse_input_part1(input)
 }
 
 #[aoc(day11, part1)]
-fn part1(universe: &DMatrix<u16>) -> usize {
-    let universe = expand_universe(universe.clone());
-    let galaxies = get_galaxies(&universe);
-    let distances = get_distances(&galaxies);
-    distances.iter().sum()
+fn part1(universe: &Universe) -> usize {
+    get_sum_of_distances(universe, 2)
 }
 
 #[aoc(day11, part2)]
-fn part2(universe: &DMatrix<u16>) -> usize {
+fn part2(universe: &Universe) -> usize {
     get_sum_of_distances(universe, 1000000)
 }
 
+/// Calculates the answer by enumerating the distances between all pairs of galaxies.
+#[aoc(day11, part2, Pairs)]
+fn part2_pairs(universe: &Universe) -> usize {
+    get_distances(&universe.expand(1000000)).iter().sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,34 +125,75 @@ mod tests {
 
     #[test]
     fn test_parse_input_part1() {
-        let mut expected = DMatrix::zeros(10, 10);
-        expected[(0, 3)] = 1;
-        expected[(1, 7)] = 2;
-        expected[(2, 0)] = 3;
-        expected[(4, 6)] = 4;
-        expected[(5, 1)] = 5;
-        expected[(6, 9)] = 6;
-        expected[(8, 7)] = 7;
-        expected[(9, 0)] = 8;
-        expected[(9, 4)] = 9;
-
-        assert_eq!(expected, parse_input_part1(get_test_input_part1()));
+        let universe = parse_input_part1(get_test_input_part1());
+        let expected = Universe {
+            galaxies: vec![
+                (3, 0),
+                (7, 1),
+                (0, 2),
+                (6, 4),
+                (1, 5),
+                (9, 6),
+                (7, 8),
+                (0, 9),
+                (4, 9),
+            ],
+            empty_rows: vec![3, 7],
+            empty_cols: vec![2, 5, 8],
+        };
+        assert_eq!(expected, universe);
+        assert_eq!(get_test_input_part1(), render(&universe.expand(1)));
 
         // Expand the universe.
-        let universe = expand_universe(expected.clone());
-
-        let mut expected = DMatrix::zeros(12, 13);
-        expected[(0, 4)] = 1;
-        expected[(1, 9)] = 2;
-        expected[(2, 0)] = 3;
-        expected[(5, 8)] = 4;
-        expected[(6, 1)] = 5;
-        expected[(7, 12)] = 6;
-        expected[(10, 9)] = 7;
-        expected[(11, 0)] = 8;
-        expected[(11, 5)] = 9;
+        let expected = indoc! {"
+            ....#........
+            .........#...
+            #............
+            .............
+            .............
+            ........#....
+            .#...........
+            ............#
+            .............
+            .............
+            .........#...
+            #....#.......
+        "};
+        assert_eq!(expected, render(&universe.expand(2)));
+        assert_eq!((9 + 3 * 99, 6 + 99), universe.expand(100)[5]);
+    }
 
-        assert_eq!(expected, universe);
+    #[test]
+    fn test_get_sum_of_distances() {
+        let universe = parse_input_part1(get_test_input_part1());
+        for growth_rate in [1, 2, 10, 100, 1000000] {
+            let galaxies = universe.expand(growth_rate);
+            let expected: usize = get_distances(&galaxies).iter().sum();
+            assert_eq!(expected, get_sum_of_distances(&universe, growth_rate));
+        }
+        assert_eq!(part2(&universe), part2_pairs(&universe));
+
+        assert_eq!(0, get_sum_of_axis_distances(vec![]));
+        assert_eq!(0, get_sum_of_axis_distances(vec![5]));
+        assert_eq!(4 + 1 + 3, get_sum_of_axis_distances(vec![7, 3, 4]));
+        assert_eq!(4, get_sum_of_axis_distances(vec![1, 1, 2, 2]));
+
+        // Galaxies on the diagonal, the distance along each axis between all pairs is the sum of
+        // (j - i) for i < j, which is (n³ - n) / 6.
+        let n = 200_000;
+        let universe = Universe {
+            galaxies: (0..n).map(|i| (i, i)).collect(),
+            empty_rows: vec![],
+            empty_cols: vec![],
+        };
+        assert_eq!((n * n * n - n) / 3, get_sum_of_distances(&universe, 2));
+    }
+
+    #[test]
+    fn test_many_galaxies() {
+        let universe = parse_input_part1(&"#".repeat(70_000));
+        assert_eq!(70_000, universe.galaxies.len());
+        assert_eq!((69_999, 0), universe.galaxies[69_999]);
     }
 
     #[test]
@@ -185,6 +214,18 @@ mod tests {
         assert_eq!(8410, get_sum_of_distances(&input, 100));
     }
 
+    fn render(galaxies: &[(usize, usize)]) -> String {
+        let width = galaxies.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+        let height = galaxies.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+        let mut rows = vec![vec!['.'; width]; height];
+        for (x, y) in galaxies {
+            rows[*y][*x] = '#';
+        }
+        rows.into_iter()
+            .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+            .collect()
+    }
+
     fn get_test_input_part1<'a>() -> &'a str {
         indoc! {"
             ...#......
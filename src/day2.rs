@@ -27,6 +27,7 @@ fn parse_input_part1(input: &str) -> HashMap<usize, Vec<Handful>> {
     let mut games: HashMap<usize, Vec<Handful>> = HashMap::new();
 
     let re = Regex::new(r"^Game (\d+): (.*)$").unwrap();
+    let color_re = Regex::new(r"(\d+) (\w+)").unwrap();
     for line in input.lines() {
         let caps = re.captures(line).unwrap();
         let game_number = caps[1].parse().unwrap();
@@ -35,8 +36,7 @@ fn parse_input_part1(input: &str) -> HashMap<usize, Vec<Handful>> {
             .map(|handful| {
                 let mut parsed_handful = Handful::new();
                 for color in handful.split(", ") {
-                    let re = Regex::new(r"(\d+) (\w+)").unwrap();
-                    let caps = re.captures(color).unwrap();
+                    let caps = color_re.captures(color).unwrap();
                     let count: usize = caps[1].parse().unwrap();
                     match &caps[2] {
                         "red" => parsed_handful.red = count,
@@ -67,8 +67,7 @@ fn part1(input: &HashMap<usize, Vec<Handful>>) -> usize {
         .filter(|(_, handfuls)| {
             let result: bool = handfuls
                 .iter()
-                .map(|handful| handful.blue <= 14 && handful.red <= 12 && handful.green <= 13)
-                .all(|x| x);
+                .all(|handful| handful.blue <= 14 && handful.red <= 12 && handful.green <= 13);
             result
         })
         .map(|(game_number, _)| game_number)
@@ -78,8 +77,8 @@ fn part1(input: &HashMap<usize, Vec<Handful>>) -> usize {
 #[aoc(day2, part2)]
 fn part2(input: &HashMap<usize, Vec<Handful>>) -> usize {
     input
-        .iter()
-        .map(|(_, handfuls)| {
+        .values()
+        .map(|handfuls| {
             let mut min_set = Handful::new();
             handfuls.iter().for_each(|handful| {
                 if handful.red > min_set.red {
@@ -1,31 +1,14 @@
+use crate::grid::{Coordinate, Grid};
 use aoc_runner_derive::{aoc, aoc_generator};
 
 #[aoc_generator(day3, part1)]
-fn parse_input_part1(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+fn parse_input_part1(input: &str) -> Grid<char> {
+    Grid::parse(input, |c| c)
 }
 
 #[aoc_generator(day3, part2)]
-fn parse_input_part2(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
-}
-
-#[derive(Clone, Copy, Debug)]
-struct Coordinate {
-    x: isize,
-    y: isize,
-}
-
-impl Coordinate {
-    fn x_distance(&self, other: &Coordinate) -> usize {
-        (self.x - other.x).unsigned_abs()
-    }
-    fn y_distance(&self, other: &Coordinate) -> usize {
-        (self.y - other.y).unsigned_abs()
-    }
-    fn is_adjacent(&self, other: &Coordinate) -> bool {
-        self.x_distance(other) <= 1 && self.y_distance(other) <= 1
-    }
+fn parse_input_part2(input: &str) -> Grid<char> {
+    parse_input_part1(input)
 }
 
 #[derive(Clone, Debug)]
@@ -34,21 +17,31 @@ struct PartNumber {
     coordinates: Vec<Coordinate>,
 }
 
+impl PartNumber {
+    /// Returns whether any of the digits of the part number touches the given coordinate, also
+    /// diagonally.
+    fn is_adjacent(&self, grid: &Grid<char>, coordinate: Coordinate) -> bool {
+        self.coordinates
+            .iter()
+            .any(|c| grid.neighbors8(*c).any(|n| n == coordinate))
+    }
+}
+
 #[derive(Clone, Debug)]
 struct Symbol {
     symbol: char,
     coordinate: Coordinate,
 }
 
-fn get_part_numbers_and_symbols(lines: &[String]) -> (Vec<PartNumber>, Vec<Symbol>) {
+fn get_part_numbers_and_symbols(grid: &Grid<char>) -> (Vec<PartNumber>, Vec<Symbol>) {
     let mut part_numbers: Vec<PartNumber> = vec![];
     let mut symbols: Vec<Symbol> = vec![];
 
-    for (y, line) in lines.iter().enumerate() {
+    for (y, line) in grid.rows().enumerate() {
         let mut number_found = false;
         let mut number = String::new();
         let mut coordinates: Vec<Coordinate> = vec![];
-        for (x, symbol) in line.chars().enumerate() {
+        for (x, &symbol) in line.iter().enumerate() {
             if symbol.is_ascii_digit() {
                 // If we are already in a number, add the digit to the number.
                 if number_found {
@@ -60,10 +53,7 @@ fn get_part_numbers_and_symbols(lines: &[String]) -> (Vec<PartNumber>, Vec<Symbo
                     coordinates = vec![];
                 }
                 // Add the coordinate to the list of coordinates.
-                coordinates.push(Coordinate {
-                    x: x.try_into().unwrap(),
-                    y: y.try_into().unwrap(),
-                });
+                coordinates.push(Coordinate { x, y });
             }
             // Current symbol is not a digit.
             else {
@@ -80,10 +70,7 @@ fn get_part_numbers_and_symbols(lines: &[String]) -> (Vec<PartNumber>, Vec<Symbo
                 if symbol != '.' {
                     symbols.push(Symbol {
                         symbol,
-                        coordinate: Coordinate {
-                            x: x.try_into().unwrap(),
-                            y: y.try_into().unwrap(),
-                        },
+                        coordinate: Coordinate { x, y },
                     });
                 }
             }
@@ -101,20 +88,17 @@ fn get_part_numbers_and_symbols(lines: &[String]) -> (Vec<PartNumber>, Vec<Symbo
 }
 
 #[aoc(day3, part1)]
-fn part1(lines: &[String]) -> usize {
+fn part1(grid: &Grid<char>) -> usize {
     // Get all part numbers and symbols.
-    let (part_numbers, symbols) = get_part_numbers_and_symbols(lines);
+    let (part_numbers, symbols) = get_part_numbers_and_symbols(grid);
 
     // Loop over all part numbers and filter out the ones that are not adjacent to any symbol.
     let adjacent_part_numbers: Vec<PartNumber> = part_numbers
         .iter()
         .filter(|part_number| {
-            symbols.iter().any(|symbol| {
-                part_number
-                    .coordinates
-                    .iter()
-                    .any(|c| c.is_adjacent(&symbol.coordinate))
-            })
+            symbols
+                .iter()
+                .any(|symbol| part_number.is_adjacent(grid, symbol.coordinate))
         })
         .cloned()
         .collect();
@@ -127,8 +111,8 @@ fn part1(lines: &[String]) -> usize {
 }
 
 #[aoc(day3, part2)]
-fn part2(lines: &[String]) -> usize {
-    let (part_numbers, symbols) = get_part_numbers_and_symbols(lines);
+fn part2(grid: &Grid<char>) -> usize {
+    let (part_numbers, symbols) = get_part_numbers_and_symbols(grid);
 
     // All gears are symbols identified by a '*'.
     let gears: Vec<Symbol> = symbols
@@ -141,12 +125,9 @@ fn part2(lines: &[String]) -> usize {
     let part_numbers: Vec<PartNumber> = part_numbers
         .iter()
         .filter(|part_number| {
-            gears.iter().any(|gear| {
-                part_number
-                    .coordinates
-                    .iter()
-                    .any(|c| c.is_adjacent(&gear.coordinate))
-            })
+            gears
+                .iter()
+                .any(|gear| part_number.is_adjacent(grid, gear.coordinate))
         })
         .cloned()
         .collect();
@@ -158,12 +139,7 @@ fn part2(lines: &[String]) -> usize {
         // Get all parts that are adjacent to the current gear.
         let adjacent_part_numbers: Vec<PartNumber> = part_numbers
             .iter()
-            .filter(|part_number| {
-                part_number
-                    .coordinates
-                    .iter()
-                    .any(|c| c.is_adjacent(&gear.coordinate))
-            })
+            .filter(|part_number| part_number.is_adjacent(grid, gear.coordinate))
             .cloned()
             .collect();
         // If there are exactly two adjacent parts, add their product to the result.
@@ -182,20 +158,12 @@ mod tests {
 
     #[test]
     fn test_parse_input_part1() {
-        let expected = vec![
-            "467..114..".to_string(),
-            "...*......".to_string(),
-            "..35..633.".to_string(),
-            "......#...".to_string(),
-            "617*......".to_string(),
-            ".....+.58.".to_string(),
-            "..592.....".to_string(),
-            "......755.".to_string(),
-            "...$.*....".to_string(),
-            ".664.598..".to_string(),
-        ];
-
-        assert_eq!(expected, parse_input_part1(get_test_input_part1()));
+        let grid = parse_input_part1(get_test_input_part1());
+        assert_eq!((10, 10), (grid.width(), grid.height()));
+        assert_eq!('4', grid[Coordinate { x: 0, y: 0 }]);
+        assert_eq!('*', grid[Coordinate { x: 3, y: 1 }]);
+        assert_eq!('.', grid[Coordinate { x: 9, y: 9 }]);
+        assert_eq!(get_test_input_part1(), grid.render(|c| *c));
     }
 
     #[test]
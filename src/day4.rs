@@ -64,7 +64,7 @@ fn parse_input_part2(input: &str) -> BTreeMap<usize, ScratchCard> {
 
 #[aoc(day4, part1)]
 fn part1(input: &BTreeMap<usize, ScratchCard>) -> usize {
-    input.iter().map(|(_, card)| card.get_score()).sum()
+    input.values().map(|card| card.get_score()).sum()
 }
 
 #[aoc(day4, part2)]
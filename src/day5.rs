@@ -1,19 +1,44 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::env;
 use std::ops::Range;
+use std::str::FromStr;
+
+/// The category of the values listed on the first line of the almanac.
+const SEED: &str = "seed";
+
+/// The category we are ultimately interested in.
+const LOCATION: &str = "location";
 
 #[derive(Clone, Debug, PartialEq)]
 struct Seed {
     id: usize,
-    soil: usize,
-    fertilizer: usize,
-    water: usize,
-    light: usize,
-    temperature: usize,
-    humidity: usize,
-    location: usize,
+    /// The value of the seed in each subsequent category, in the order of the almanac.
+    values: Vec<(String, usize)>,
+}
+
+impl Seed {
+    fn get_value(&self, category: &str) -> Option<usize> {
+        self.values
+            .iter()
+            .find(|(c, _)| c == category)
+            .map(|(_, value)| *value)
+    }
+
+    fn get_location(&self) -> usize {
+        self.get_value(LOCATION).unwrap()
+    }
+}
+
+/// A single step in the conversion of a value from one category to the next.
+#[derive(Clone, Debug, PartialEq)]
+struct TraceStep {
+    category: String,
+    value: usize,
+    /// The index of the map row that was hit, or `None` if the value was passed through unchanged.
+    row: Option<usize>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -28,61 +53,372 @@ impl Maps {
         }
     }
 
-    fn get_mapped_value(&self, map_type: MapType, i: usize) -> usize {
-        let mut value = i;
-        for [dest_start, src_start, len] in self.maps.get(&map_type).unwrap() {
+    /// Returns the index of the map row that maps the given value, or `None` if the value is not
+    /// covered by any row and is mapped onto itself. If rows overlap, the last one wins.
+    fn get_mapped_row(&self, map_type: &MapType, i: usize) -> Option<usize> {
+        self.maps
+            .get(map_type)
+            .unwrap()
+            .iter()
+            .rposition(|[_, src_start, len]| (*src_start..(*src_start + *len)).contains(&i))
+    }
+
+    fn get_mapped_value(&self, map_type: &MapType, i: usize) -> usize {
+        match self.get_mapped_row(map_type, i) {
+            Some(row) => {
+                let [dest_start, src_start, _] = self.maps.get(map_type).unwrap()[row];
+                dest_start + i - src_start
+            }
+            None => i,
+        }
+    }
+
+    /// Maps a list of ranges through a single map. Each range is split against the source ranges
+    /// of the map rows, so that every resulting range is mapped in one go. Parts of ranges that
+    /// are not covered by any row are passed through unchanged. If rows overlap, the last one
+    /// wins, so the rows are applied from last to first.
+    fn get_mapped_ranges(
+        &self,
+        map_type: &MapType,
+        ranges: Vec<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        let mut mapped = vec![];
+        let mut unmapped: Vec<Range<usize>> =
+            ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        for [dest_start, src_start, len] in self.maps.get(map_type).unwrap().iter().rev() {
             let src_range = *src_start..(*src_start + *len);
-            if src_range.contains(&i) {
-                value = dest_start + i - src_start;
+            let mut remaining = vec![];
+            for range in unmapped {
+                // The overlapping part is shifted to the destination, the rest is not mapped by
+                // this row.
+                let (overlap, mut rest) = split_range(&range, &src_range);
+                if let Some(o) = overlap {
+                    mapped.push(dest_start + o.start - src_start..dest_start + o.end - src_start);
+                }
+                remaining.append(&mut rest);
+            }
+            unmapped = remaining;
+        }
+        mapped.append(&mut unmapped);
+        mapped
+    }
+
+    /// Performs the inverse of `get_mapped_ranges()`: returns the source ranges which are mapped
+    /// onto the given destination ranges by a single map.
+    fn get_source_ranges(
+        &self,
+        map_type: &MapType,
+        ranges: Vec<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        let rows = self.maps.get(map_type).unwrap();
+        let ranges: Vec<Range<usize>> = ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        let mut sources = vec![];
+
+        // Values in the destination range of a row originate from the source range of that row,
+        // except for the source values that are taken by a later row.
+        for (i, [dest_start, src_start, len]) in rows.iter().enumerate() {
+            let dest_range = *dest_start..(*dest_start + *len);
+            for range in &ranges {
+                if let (Some(o), _) = split_range(range, &dest_range) {
+                    let source = src_start + o.start - dest_start..src_start + o.end - dest_start;
+                    let mut source = rows[i + 1..].iter().fold(
+                        vec![source],
+                        |source, [_, later_start, later_len]| {
+                            let later_range = *later_start..(*later_start + *later_len);
+                            source
+                                .iter()
+                                .flat_map(|range| split_range(range, &later_range).1)
+                                .collect()
+                        },
+                    );
+                    sources.append(&mut source);
+                }
             }
         }
-        value
+
+        // Values that are not in the source range of any row are mapped onto themselves.
+        let mut unmapped = ranges;
+        for [_, src_start, len] in rows {
+            let src_range = *src_start..(*src_start + *len);
+            unmapped = unmapped
+                .iter()
+                .flat_map(|range| split_range(range, &src_range).1)
+                .collect();
+        }
+        sources.append(&mut unmapped);
+        sources
+    }
+
+    /// Returns the maps that need to be applied in order to convert a value from one category to
+    /// another, or `None` if the almanac doesn't link the two categories.
+    fn get_chain(&self, from: &str, to: &str) -> Option<Vec<MapType>> {
+        // Do a breadth-first search starting from the source category, keeping track of the map
+        // through which each category was reached.
+        let mut reached_through: HashMap<&str, &MapType> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+            for map_type in self.maps.keys().filter(|m| m.source == category) {
+                let destination = map_type.destination.as_str();
+                if destination != from && !reached_through.contains_key(destination) {
+                    reached_through.insert(destination, map_type);
+                    queue.push_back(destination);
+                }
+            }
+        }
+
+        // Walk back from the destination category to build the chain.
+        let mut chain = vec![];
+        let mut category = to;
+        while category != from {
+            let map_type = reached_through.get(category)?;
+            chain.push((*map_type).clone());
+            category = &map_type.source;
+        }
+        chain.reverse();
+        Some(chain)
+    }
+
+    /// Converts a value from one category to another, recording the value in each intermediate
+    /// category and the map row that was used to get there.
+    fn trace(&self, from: &str, to: &str, value: usize) -> Option<Vec<TraceStep>> {
+        let chain = self.get_chain(from, to)?;
+        let mut value = value;
+        let mut steps = vec![];
+        for map_type in chain {
+            let row = self.get_mapped_row(&map_type, value);
+            value = self.get_mapped_value(&map_type, value);
+            steps.push(TraceStep {
+                category: map_type.destination,
+                value,
+                row,
+            });
+        }
+        Some(steps)
+    }
+
+    /// Converts a value from one category to another, or returns `None` if the almanac doesn't
+    /// link the two categories.
+    fn convert(&self, from: &str, to: &str, value: usize) -> Option<usize> {
+        let steps = self.trace(from, to, value)?;
+        Some(steps.last().map_or(value, |step| step.value))
+    }
+
+    /// Maps a list of seed ranges to location ranges. The almanac should link seeds to locations.
+    fn map_ranges(&self, ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+        self.convert_ranges(SEED, LOCATION, ranges)
+            .expect("The almanac doesn't link seeds to locations.")
+    }
+
+    /// Converts a list of ranges from one category to another.
+    fn convert_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: Vec<Range<usize>>,
+    ) -> Option<Vec<Range<usize>>> {
+        let chain = self.get_chain(from, to)?;
+        Some(chain.iter().fold(ranges, |ranges, map_type| {
+            self.get_mapped_ranges(map_type, ranges)
+        }))
     }
-    fn get_soil(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::SeedToSoil, seed)
+
+    /// Performs the inverse of `convert_ranges()`: returns the ranges in the `from` category that
+    /// end up in the given ranges of the `to` category.
+    fn convert_ranges_reverse(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: Vec<Range<usize>>,
+    ) -> Option<Vec<Range<usize>>> {
+        let chain = self.get_chain(from, to)?;
+        Some(chain.iter().rev().fold(ranges, |ranges, map_type| {
+            self.get_source_ranges(map_type, ranges)
+        }))
     }
-    fn get_fertilizer(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::SoilToFertilizer, self.get_soil(seed))
+
+    /// Returns an upper bound for all values in the almanac. Values at or above this bound are
+    /// not touched by any of the maps.
+    fn get_upper_bound(&self) -> usize {
+        self.maps
+            .values()
+            .flatten()
+            .map(|[dest_start, src_start, len]| dest_start.max(src_start) + len)
+            .max()
+            .unwrap_or(0)
     }
-    fn get_water(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::FertilizerToWater, self.get_fertilizer(seed))
+}
+
+/// Splits a range against another range. Returns the overlapping part, if any, and the parts of
+/// the range that lie before and after the other range.
+fn split_range(
+    range: &Range<usize>,
+    other: &Range<usize>,
+) -> (Option<Range<usize>>, Vec<Range<usize>>) {
+    let mut rest = vec![];
+    if range.start < other.start {
+        rest.push(range.start..range.end.min(other.start));
     }
-    fn get_light(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::WaterToLight, self.get_water(seed))
+    if range.end > other.end {
+        rest.push(range.start.max(other.end)..range.end);
     }
-    fn get_temperature(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::LightToTemperature, self.get_light(seed))
+    let start = range.start.max(other.start);
+    let end = range.end.min(other.end);
+    let overlap = if start < end { Some(start..end) } else { None };
+    (overlap, rest)
+}
+
+/// Finds the lowest location for the given seed ranges by working backwards from the locations.
+/// This does a binary search for the smallest range of locations `0..n` which contains a location
+/// that originates from one of the seeds.
+fn get_lowest_location_by_reverse_lookup(maps: &Maps, seed_ranges: &[Range<usize>]) -> usize {
+    let contains_seed = |n: usize| {
+        let locations = 0..n;
+        maps.convert_ranges_reverse(SEED, LOCATION, vec![locations])
+            .unwrap()
+            .iter()
+            .any(|range| {
+                seed_ranges
+                    .iter()
+                    .any(|seed_range| split_range(range, seed_range).0.is_some())
+            })
+    };
+
+    // All seeds end up in a location below the upper bound of the almanac.
+    let upper_bound = seed_ranges
+        .iter()
+        .map(|range| range.end)
+        .max()
+        .unwrap()
+        .max(maps.get_upper_bound());
+    let (mut low, mut high) = (0, upper_bound);
+    while low < high {
+        let mid = (low + high) / 2;
+        if contains_seed(mid + 1) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
     }
-    fn get_humidity(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::TemperatureToHumidity, self.get_temperature(seed))
+    low
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReportFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(ReportFormat::Table),
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("Unknown report format: {}", s)),
+        }
     }
-    fn get_location(&self, seed: usize) -> usize {
-        self.get_mapped_value(MapType::HumidityToLocation, self.get_humidity(seed))
+}
+
+/// Returns a report tracing each seed through the almanac, listing the value in every category
+/// together with the map row that was used. Identity fallthroughs are marked with `-`.
+fn get_trace_report(maps: &Maps, seed_ids: &[usize], format: ReportFormat) -> String {
+    let traces: Vec<(usize, Vec<TraceStep>)> = seed_ids
+        .iter()
+        .map(|id| (*id, maps.trace(SEED, LOCATION, *id).unwrap()))
+        .collect();
+    let categories: Vec<&str> = traces
+        .first()
+        .map(|(_, steps)| steps.iter().map(|s| s.category.as_str()).collect())
+        .unwrap_or_default();
+    let format_row = |row: Option<usize>| row.map_or("-".to_string(), |r| r.to_string());
+
+    match format {
+        ReportFormat::Table | ReportFormat::Csv => {
+            // Build a grid of cells, starting with the header.
+            let mut cells: Vec<Vec<String>> = vec![];
+            let mut header = vec![SEED.to_string()];
+            for category in &categories {
+                header.push(category.to_string());
+                if format == ReportFormat::Csv {
+                    header.push(format!("{}_row", category));
+                }
+            }
+            cells.push(header);
+            for (id, steps) in &traces {
+                let mut line = vec![id.to_string()];
+                for step in steps {
+                    match format {
+                        ReportFormat::Csv => {
+                            line.push(step.value.to_string());
+                            line.push(step.row.map_or(String::new(), |r| r.to_string()));
+                        }
+                        _ => line.push(format!("{} ({})", step.value, format_row(step.row))),
+                    }
+                }
+                cells.push(line);
+            }
+
+            if format == ReportFormat::Csv {
+                return cells.iter().map(|line| line.join(",") + "\n").collect();
+            }
+
+            // Pad each column to the width of its widest cell.
+            let widths: Vec<usize> = (0..cells[0].len())
+                .map(|i| cells.iter().map(|line| line[i].len()).max().unwrap())
+                .collect();
+            cells
+                .iter()
+                .map(|line| {
+                    let padded: Vec<String> = line
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                        .collect();
+                    padded.join(" | ").trim_end().to_string() + "\n"
+                })
+                .collect()
+        }
+        ReportFormat::Json => {
+            let seeds: Vec<String> = traces
+                .iter()
+                .map(|(id, steps)| {
+                    let steps: Vec<String> = steps
+                        .iter()
+                        .map(|step| {
+                            format!(
+                                "{{\"category\":\"{}\",\"value\":{},\"row\":{}}}",
+                                step.category.replace('\\', "\\\\").replace('"', "\\\""),
+                                step.value,
+                                step.row.map_or("null".to_string(), |r| r.to_string())
+                            )
+                        })
+                        .collect();
+                    format!("{{\"seed\":{},\"steps\":[{}]}}", id, steps.join(","))
+                })
+                .collect();
+            format!("[{}]\n", seeds.join(","))
+        }
     }
 }
 
+/// Identifies a map by the category it converts from and the category it converts to.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-enum MapType {
-    SeedToSoil,
-    SoilToFertilizer,
-    FertilizerToWater,
-    WaterToLight,
-    LightToTemperature,
-    TemperatureToHumidity,
-    HumidityToLocation,
+struct MapType {
+    source: String,
+    destination: String,
 }
 
 impl MapType {
-    fn from_string(s: &str) -> MapType {
-        match s {
-            "seed-to-soil" => MapType::SeedToSoil,
-            "soil-to-fertilizer" => MapType::SoilToFertilizer,
-            "fertilizer-to-water" => MapType::FertilizerToWater,
-            "water-to-light" => MapType::WaterToLight,
-            "light-to-temperature" => MapType::LightToTemperature,
-            "temperature-to-humidity" => MapType::TemperatureToHumidity,
-            "humidity-to-location" => MapType::HumidityToLocation,
-            _ => unreachable!(),
+    fn new(source: &str, destination: &str) -> MapType {
+        MapType {
+            source: source.to_string(),
+            destination: destination.to_string(),
         }
     }
 }
@@ -94,7 +430,7 @@ fn get_maps_from_input(input: &str) -> Maps {
 
     let mut maps = Maps::new();
 
-    let new_map_re = Regex::new(r"^(.*) map:$").unwrap();
+    let new_map_re = Regex::new(r"^(\S+)-to-(\S+) map:$").unwrap();
     let mut current_mapping: Option<MapType> = None;
     for line in lines {
         if line.is_empty() {
@@ -103,7 +439,7 @@ fn get_maps_from_input(input: &str) -> Maps {
 
         // Detect if we are starting a new map.
         if let Some(caps) = new_map_re.captures(line) {
-            current_mapping = Some(MapType::from_string(&caps[1]));
+            current_mapping = Some(MapType::new(&caps[1], &caps[2]));
             continue;
         }
 
@@ -123,7 +459,7 @@ fn get_maps_from_input(input: &str) -> Maps {
 }
 
 #[aoc_generator(day5, part1)]
-fn parse_input_part1(input: &str) -> (Vec<Seed>, Maps) {
+fn parse_input_part1(input: &str) -> Result<(Vec<Seed>, Maps), String> {
     // Get the first line, which contains the seed IDs.
     let mut lines = input.lines();
     let seedline = lines.next().unwrap();
@@ -137,25 +473,26 @@ fn parse_input_part1(input: &str) -> (Vec<Seed>, Maps) {
     // Populate the maps.
     let maps = get_maps_from_input(input);
 
-    let mut seeds: Vec<Seed> = vec![];
-    for seed_id in seed_ids {
-        seeds.append(&mut vec![Seed {
-            id: seed_id,
-            soil: maps.get_soil(seed_id),
-            fertilizer: maps.get_fertilizer(seed_id),
-            water: maps.get_water(seed_id),
-            light: maps.get_light(seed_id),
-            temperature: maps.get_temperature(seed_id),
-            humidity: maps.get_humidity(seed_id),
-            location: maps.get_location(seed_id),
-        }]);
-    }
-
-    (seeds, maps)
+    // Convert each seed through the chain of maps, keeping track of the intermediate values.
+    let mut seeds = vec![];
+    for id in seed_ids {
+        let values = maps
+            .trace(SEED, LOCATION, id)
+            .ok_or(format!(
+                "The almanac doesn't link {} to {}.",
+                SEED, LOCATION
+            ))?
+            .into_iter()
+            .map(|step| (step.category, step.value))
+            .collect();
+        seeds.push(Seed { id, values });
+    }
+
+    Ok((seeds, maps))
 }
 
 #[aoc_generator(day5, part2)]
-fn parse_input_part2(input: &str) -> (Vec<Range<usize>>, Maps) {
+fn parse_input_part2(input: &str) -> Result<(Vec<Range<usize>>, Maps), String> {
     // Get the first line, which contains the seed IDs.
     let mut lines = input.lines();
     let seedline = lines.next().unwrap();
@@ -174,8 +511,14 @@ fn parse_input_part2(input: &str) -> (Vec<Range<usize>>, Maps) {
 
     // Populate the maps.
     let maps = get_maps_from_input(input);
+    if maps.get_chain(SEED, LOCATION).is_none() {
+        return Err(format!(
+            "The almanac doesn't link {} to {}.",
+            SEED, LOCATION
+        ));
+    }
 
-    (seed_ranges, maps)
+    Ok((seed_ranges, maps))
 }
 
 #[aoc(day5, part1)]
@@ -183,44 +526,56 @@ fn part1(input: &(Vec<Seed>, Maps)) -> usize {
     let seeds = input.0.clone();
     let seed = seeds
         .iter()
-        .sorted_by(|a, b| Ord::cmp(&a.location, &b.location))
+        .sorted_by(|a, b| Ord::cmp(&a.get_location(), &b.get_location()))
         .next()
         .unwrap();
-    seed.location
+    seed.get_location()
 }
 
 #[aoc(day5, part2)]
 fn part2(input: &(Vec<Range<usize>>, Maps)) -> usize {
-    let seed_ranges = input.0.clone();
-    let maps = &input.1;
-    let mut closest_seed: Option<Seed> = None;
-    // Loop through each seed range.
-    for seed_range in seed_ranges {
-        // Loop through each seed in the range.
-        // Todo: Find a different way, the ranges are huge.
-        for seed_id in seed_range {
-            let seed = Seed {
-                id: seed_id,
-                soil: maps.get_soil(seed_id),
-                fertilizer: maps.get_fertilizer(seed_id),
-                water: maps.get_water(seed_id),
-                light: maps.get_light(seed_id),
-                temperature: maps.get_temperature(seed_id),
-                humidity: maps.get_humidity(seed_id),
-                location: maps.get_location(seed_id),
-            };
-            // If this is the first seed, set it as the closest seed.
-            if closest_seed.is_none() {
-                closest_seed = Some(seed);
-                continue;
-            }
-            // If this seed is closer than the current closest seed, set it as the closest seed.
-            if seed.location < closest_seed.clone().unwrap().location {
-                closest_seed = Some(seed);
-            }
-        }
+    let (seed_ranges, maps) = input;
+    // Map the seed ranges to location ranges, and return the lowest location.
+    maps.map_ranges(seed_ranges.clone())
+        .iter()
+        .map(|range| range.start)
+        .min()
+        .unwrap()
+}
+
+/// Finds the lowest location by working backwards from the locations, and cross-checks it by
+/// converting the seeds forward.
+#[aoc(day5, part1, Reverse)]
+fn part1_reverse(input: &(Vec<Seed>, Maps)) -> Result<usize, String> {
+    let (seeds, maps) = input;
+    let seed_ranges: Vec<Range<usize>> = seeds.iter().map(|seed| seed.id..seed.id + 1).collect();
+    let location = get_lowest_location_by_reverse_lookup(maps, &seed_ranges);
+    match seeds
+        .iter()
+        .any(|seed| maps.convert(SEED, LOCATION, seed.id) == Some(location))
+    {
+        true => Ok(location),
+        false => Err(format!("No seed yields location {}.", location)),
     }
-    closest_seed.unwrap().location
+}
+
+/// Prints a trace of every seed through the almanac. The format can be chosen by setting the
+/// `DAY5_REPORT_FORMAT` environment variable to `table` (the default), `csv` or `json`.
+#[aoc(day5, part1, Trace)]
+fn part1_trace(input: &(Vec<Seed>, Maps)) -> Result<String, String> {
+    let (seeds, maps) = input;
+    let format = match env::var("DAY5_REPORT_FORMAT") {
+        Ok(format) => format.parse()?,
+        Err(_) => ReportFormat::Table,
+    };
+    let seed_ids: Vec<usize> = seeds.iter().map(|seed| seed.id).collect();
+    Ok(format!("\n{}", get_trace_report(maps, &seed_ids, format)))
+}
+
+#[aoc(day5, part2, Reverse)]
+fn part2_reverse(input: &(Vec<Range<usize>>, Maps)) -> usize {
+    let (seed_ranges, maps) = input;
+    get_lowest_location_by_reverse_lookup(maps, seed_ranges)
 }
 
 #[cfg(test)]
@@ -233,67 +588,55 @@ mod tests {
         let seeds = vec![
             Seed {
                 id: 79,
-                soil: 81,
-                fertilizer: 81,
-                water: 81,
-                light: 74,
-                temperature: 78,
-                humidity: 78,
-                location: 82,
+                values: get_values([81, 81, 81, 74, 78, 78, 82]),
             },
             Seed {
                 id: 14,
-                soil: 14,
-                fertilizer: 53,
-                water: 49,
-                light: 42,
-                temperature: 42,
-                humidity: 43,
-                location: 43,
+                values: get_values([14, 53, 49, 42, 42, 43, 43]),
             },
             Seed {
                 id: 55,
-                soil: 57,
-                fertilizer: 57,
-                water: 53,
-                light: 46,
-                temperature: 82,
-                humidity: 82,
-                location: 86,
+                values: get_values([57, 57, 53, 46, 82, 82, 86]),
             },
             Seed {
                 id: 13,
-                soil: 13,
-                fertilizer: 52,
-                water: 41,
-                light: 34,
-                temperature: 34,
-                humidity: 35,
-                location: 35,
+                values: get_values([13, 52, 41, 34, 34, 35, 35]),
             },
         ];
 
         let mut maps = HashMap::new();
-        maps.insert(MapType::SeedToSoil, vec![[50, 98, 2], [52, 50, 48]]);
         maps.insert(
-            MapType::SoilToFertilizer,
+            MapType::new("seed", "soil"),
+            vec![[50, 98, 2], [52, 50, 48]],
+        );
+        maps.insert(
+            MapType::new("soil", "fertilizer"),
             vec![[0, 15, 37], [37, 52, 2], [39, 0, 15]],
         );
         maps.insert(
-            MapType::FertilizerToWater,
+            MapType::new("fertilizer", "water"),
             vec![[49, 53, 8], [0, 11, 42], [42, 0, 7], [57, 7, 4]],
         );
-        maps.insert(MapType::WaterToLight, vec![[88, 18, 7], [18, 25, 70]]);
         maps.insert(
-            MapType::LightToTemperature,
+            MapType::new("water", "light"),
+            vec![[88, 18, 7], [18, 25, 70]],
+        );
+        maps.insert(
+            MapType::new("light", "temperature"),
             vec![[45, 77, 23], [81, 45, 19], [68, 64, 13]],
         );
-        maps.insert(MapType::TemperatureToHumidity, vec![[0, 69, 1], [1, 0, 69]]);
-        maps.insert(MapType::HumidityToLocation, vec![[60, 56, 37], [56, 93, 4]]);
+        maps.insert(
+            MapType::new("temperature", "humidity"),
+            vec![[0, 69, 1], [1, 0, 69]],
+        );
+        maps.insert(
+            MapType::new("humidity", "location"),
+            vec![[60, 56, 37], [56, 93, 4]],
+        );
 
         assert_eq!(
             (seeds, Maps { maps }),
-            parse_input_part1(get_test_input_part1())
+            parse_input_part1(get_test_input_part1()).unwrap()
         );
     }
 
@@ -302,39 +645,272 @@ mod tests {
         let seed_ranges = vec![79..93, 55..68];
 
         let mut maps = HashMap::new();
-        maps.insert(MapType::SeedToSoil, vec![[50, 98, 2], [52, 50, 48]]);
         maps.insert(
-            MapType::SoilToFertilizer,
+            MapType::new("seed", "soil"),
+            vec![[50, 98, 2], [52, 50, 48]],
+        );
+        maps.insert(
+            MapType::new("soil", "fertilizer"),
             vec![[0, 15, 37], [37, 52, 2], [39, 0, 15]],
         );
         maps.insert(
-            MapType::FertilizerToWater,
+            MapType::new("fertilizer", "water"),
             vec![[49, 53, 8], [0, 11, 42], [42, 0, 7], [57, 7, 4]],
         );
-        maps.insert(MapType::WaterToLight, vec![[88, 18, 7], [18, 25, 70]]);
         maps.insert(
-            MapType::LightToTemperature,
+            MapType::new("water", "light"),
+            vec![[88, 18, 7], [18, 25, 70]],
+        );
+        maps.insert(
+            MapType::new("light", "temperature"),
             vec![[45, 77, 23], [81, 45, 19], [68, 64, 13]],
         );
-        maps.insert(MapType::TemperatureToHumidity, vec![[0, 69, 1], [1, 0, 69]]);
-        maps.insert(MapType::HumidityToLocation, vec![[60, 56, 37], [56, 93, 4]]);
+        maps.insert(
+            MapType::new("temperature", "humidity"),
+            vec![[0, 69, 1], [1, 0, 69]],
+        );
+        maps.insert(
+            MapType::new("humidity", "location"),
+            vec![[60, 56, 37], [56, 93, 4]],
+        );
 
         assert_eq!(
             (seed_ranges, Maps { maps }),
-            parse_input_part2(get_test_input_part2())
+            parse_input_part2(get_test_input_part2()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_map_ranges() {
+        let maps = parse_input_part1(get_test_input_part1()).unwrap().1;
+
+        // A range spanning a map boundary is split in two. Ranges that are not covered by any map
+        // are passed through unchanged.
+        assert_eq!(
+            vec![98..100, 50..52, 0..10],
+            maps.get_mapped_ranges(&MapType::new("seed", "soil"), vec![0..10, 96..100])
+        );
+
+        // Mapping a range should cover exactly the same locations as mapping each seed.
+        let (seed_ranges, maps) = parse_input_part2(get_test_input_part2()).unwrap();
+        let mut expected: Vec<usize> = seed_ranges
+            .iter()
+            .flat_map(|range| {
+                range
+                    .clone()
+                    .map(|id| maps.convert(SEED, LOCATION, id).unwrap())
+            })
+            .collect();
+        expected.sort();
+        let mut actual: Vec<usize> = maps.map_ranges(seed_ranges).into_iter().flatten().collect();
+        actual.sort();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_unmap_ranges() {
+        let (seeds, maps) = parse_input_part1(get_test_input_part1()).unwrap();
+
+        // Looking up the location of each seed should lead back to the seed.
+        let locations: Vec<Range<usize>> = seeds
+            .iter()
+            .map(|seed| seed.get_location()..seed.get_location() + 1)
+            .collect();
+        let seed_ids: Vec<usize> = maps
+            .convert_ranges_reverse(SEED, LOCATION, locations)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        for seed in &seeds {
+            assert!(seed_ids.contains(&seed.id));
+        }
+
+        // Every seed found by the reverse lookup should map forward onto the same locations.
+        let locations = vec![0..10, 40..50, 80..90];
+        for seed_id in maps
+            .convert_ranges_reverse(SEED, LOCATION, locations.clone())
+            .unwrap()
+            .into_iter()
+            .flatten()
+        {
+            let location = maps.convert(SEED, LOCATION, seed_id).unwrap();
+            assert!(locations.iter().any(|range| range.contains(&location)));
+        }
+
+        // A location can be reached from more than one source value: 50 is mapped from 98, and
+        // since 50 is itself covered by a row it is not passed through unchanged.
+        assert_eq!(
+            vec![98..99, 48..49],
+            maps.get_source_ranges(&MapType::new("seed", "soil"), vec![50..51, 48..49])
+        );
+    }
+
+    #[test]
+    fn test_overlapping_rows() {
+        // When rows overlap the last one wins, whether we map values, ranges or look them up in
+        // reverse.
+        let input = indoc! {"
+            seeds: 5 1
+
+            seed-to-location map:
+            100 0 10
+            200 0 10
+        "};
+        let map_type = MapType::new(SEED, LOCATION);
+        let input1 = parse_input_part1(input).unwrap();
+        let input2 = parse_input_part2(input).unwrap();
+        assert_eq!(Some(1), input1.1.get_mapped_row(&map_type, 5));
+        assert_eq!(201, part1(&input1));
+        assert_eq!(Ok(201), part1_reverse(&input1));
+        assert_eq!(205, part2(&input2));
+        assert_eq!(205, part2_reverse(&input2));
+
+        let maps = input1.1;
+        assert_eq!(
+            vec![200..210, 10..20],
+            maps.get_mapped_ranges(&map_type, vec![0..10, 10..20])
+        );
+        // The source values of the first row are taken by the second row, so the destination of
+        // the first row can only be reached by values that pass through unchanged.
+        assert_eq!(
+            vec![0..10, 100..110, 200..210],
+            maps.get_source_ranges(&map_type, vec![100..110, 200..210])
         );
     }
 
+    #[test]
+    fn test_convert() {
+        let (seeds, maps) = parse_input_part1(get_test_input_part1()).unwrap();
+
+        // Values can be converted between any two linked categories.
+        for seed in &seeds {
+            let water = seed.get_value("water").unwrap();
+            let humidity = seed.get_value("humidity").unwrap();
+            assert_eq!(Some(humidity), maps.convert("water", "humidity", water));
+            assert_eq!(Some(water), maps.convert("water", "water", water));
+        }
+
+        // Categories can only be converted in the direction of the maps.
+        assert_eq!(None, maps.convert("location", "seed", 82));
+        assert_eq!(None, maps.convert("seed", "altitude", 79));
+
+        // Almanacs with renamed or additional stages are supported.
+        let maps = get_maps_from_input(indoc! {"
+            seeds: 79 14 55 13
+
+            seed-to-mineral map:
+            50 98 2
+            52 50 48
+
+            mineral-to-location map:
+            0 15 37
+            37 52 2
+            39 0 15
+
+            location-to-altitude map:
+            10 0 100
+        "});
+        assert_eq!(Some(81), maps.convert(SEED, "mineral", 79));
+        assert_eq!(Some(81), maps.convert(SEED, LOCATION, 79));
+        assert_eq!(Some(91), maps.convert(SEED, "altitude", 79));
+        assert_eq!(
+            Some(vec![45..47, 91..95]),
+            maps.convert_ranges(SEED, "altitude", vec![79..83, 98..100])
+        );
+
+        // The seeds are traced through renamed stages, but they need to end up in a location.
+        let (seeds, _) = parse_input_part1(indoc! {"
+            seeds: 79
+
+            seed-to-mineral map:
+            50 98 2
+            52 50 48
+
+            mineral-to-location map:
+            0 15 37
+        "})
+        .unwrap();
+        assert_eq!(Some(81), seeds[0].get_value("mineral"));
+        assert_eq!(81, seeds[0].get_location());
+        let input = indoc! {"
+            seeds: 79 1
+
+            seed-to-mineral map:
+            50 98 2
+
+            mineral-to-altitude map:
+            0 15 37
+        "};
+        let expected = "The almanac doesn't link seed to location.".to_string();
+        assert_eq!(Err(expected.clone()), parse_input_part1(input));
+        assert_eq!(Err(expected), parse_input_part2(input));
+    }
+
+    #[test]
+    fn test_trace_report() {
+        let maps = parse_input_part1(get_test_input_part1()).unwrap().1;
+
+        let expected = indoc! {"
+            seed | soil   | fertilizer | water  | light  | temperature | humidity | location
+            79   | 81 (1) | 81 (-)     | 81 (-) | 74 (1) | 78 (2)      | 78 (-)   | 82 (0)
+            14   | 14 (-) | 53 (2)     | 49 (0) | 42 (1) | 42 (-)      | 43 (1)   | 43 (-)
+        "};
+        assert_eq!(
+            expected,
+            get_trace_report(&maps, &[79, 14], ReportFormat::Table)
+        );
+
+        let expected = indoc! {"
+            seed,soil,soil_row,fertilizer,fertilizer_row,water,water_row,light,light_row,temperature,temperature_row,humidity,humidity_row,location,location_row
+            79,81,1,81,,81,,74,1,78,2,78,,82,0
+        "};
+        assert_eq!(expected, get_trace_report(&maps, &[79], ReportFormat::Csv));
+
+        let expected = concat!(
+            r#"[{"seed":13,"steps":["#,
+            r#"{"category":"soil","value":13,"row":null},"#,
+            r#"{"category":"fertilizer","value":52,"row":2},"#,
+            r#"{"category":"water","value":41,"row":1},"#,
+            r#"{"category":"light","value":34,"row":1},"#,
+            r#"{"category":"temperature","value":34,"row":null},"#,
+            r#"{"category":"humidity","value":35,"row":1},"#,
+            r#"{"category":"location","value":35,"row":null}]}]"#,
+            "\n"
+        );
+        assert_eq!(expected, get_trace_report(&maps, &[13], ReportFormat::Json));
+    }
+
     #[test]
     fn part1_example() {
-        let input = parse_input_part1(get_test_input_part1());
+        let input = parse_input_part1(get_test_input_part1()).unwrap();
         assert_eq!(35, part1(&input));
+        assert_eq!(Ok(35), part1_reverse(&input));
     }
 
     #[test]
     fn part2_example() {
-        let input = parse_input_part2(get_test_input_part2());
+        let input = parse_input_part2(get_test_input_part2()).unwrap();
         assert_eq!(46, part2(&input));
+        assert_eq!(46, part2_reverse(&input));
+    }
+
+    /// Returns the values of a seed in each category of the example almanac.
+    fn get_values(values: [usize; 7]) -> Vec<(String, usize)> {
+        let categories = [
+            "soil",
+            "fertilizer",
+            "water",
+            "light",
+            "temperature",
+            "humidity",
+            "location",
+        ];
+        categories
+            .iter()
+            .map(|c| c.to_string())
+            .zip(values)
+            .collect()
     }
 
     fn get_test_input_part1<'a>() -> &'a str {
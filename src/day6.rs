@@ -1,4 +1,5 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use std::ops::RangeInclusive;
 
 #[derive(Debug, PartialEq)]
 struct Race {
@@ -7,15 +8,95 @@ struct Race {
 }
 
 impl Race {
-    fn get_number_of_winners(&self) -> usize {
-        let rt = self.time as f64;
-        let d = self.distance;
+    /// Returns the distance travelled when charging the boat for the given time.
+    fn get_distance(&self, charge_time: usize) -> u128 {
+        charge_time as u128 * (self.time - charge_time) as u128
+    }
+
+    /// Returns the margin by which the given charge time beats the record. This is negative if the
+    /// record is not beaten.
+    fn get_margin(&self, charge_time: usize) -> i128 {
+        self.get_distance(charge_time) as i128 - self.distance as i128
+    }
+
+    /// Returns the charge time that results in the longest distance.
+    fn get_optimal_charge_time(&self) -> usize {
+        // The distance is a parabola which peaks at half of the race time.
+        self.time / 2
+    }
+
+    fn get_max_distance(&self) -> u128 {
+        self.get_distance(self.get_optimal_charge_time())
+    }
+
+    /// Returns the range of charge times that beat the record, or `None` if it can't be beaten.
+    fn get_winning_charge_times(&self) -> Option<RangeInclusive<usize>> {
+        let rt = self.time as u128;
+        let d = self.distance as u128;
 
         // distance = (race_time - charge_time) * charge_time
         // distance = race_time * charge_time - charge_time ^ 2
         // charge_time = (race_time - sqrt(race_time ^ 2 - 4 * distance)) / 2
-        let ct = (rt - f64::sqrt((rt * rt) - (4 * d) as f64)) / 2.0;
-        self.time - ct.floor() as usize - (ct.floor() as usize + 1)
+        // The calculation is done in integers so we don't lose precision on large races.
+        let discriminant = (rt * rt).checked_sub(4 * d).filter(|d| *d > 0)?;
+        let mut ct = ((rt - discriminant.isqrt()) / 2) as usize;
+
+        // The integer square root is rounded down, so the charge time might be off by one. Adjust
+        // it to the lowest charge time that beats the distance.
+        let optimal = self.get_optimal_charge_time();
+        while ct > 0 && self.get_margin(ct - 1) > 0 {
+            ct -= 1;
+        }
+        while ct <= optimal && self.get_margin(ct) <= 0 {
+            ct += 1;
+        }
+        if ct > optimal {
+            return None;
+        }
+
+        // The winning charge times are symmetrical around half of the race time.
+        Some(ct..=self.time - ct)
+    }
+
+    fn get_number_of_winners(&self) -> usize {
+        self.get_winning_charge_times().map_or(0, |charge_times| {
+            charge_times.end() - charge_times.start() + 1
+        })
+    }
+
+    /// Returns a breakdown of the race, listing the margin for each winning charge time if there
+    /// are not more than the given number of them.
+    fn get_breakdown(&self, max_listed: usize) -> String {
+        let mut breakdown = format!(
+            "Race of {} ms with a record of {} mm: ",
+            self.time, self.distance
+        );
+        let charge_times = match self.get_winning_charge_times() {
+            Some(charge_times) => charge_times,
+            None => {
+                breakdown.push_str("the record can't be beaten.\n");
+                return breakdown;
+            }
+        };
+        breakdown.push_str(&format!(
+            "charge for {} to {} ms to win in {} ways, the optimal charge time of {} ms reaches {} mm.\n",
+            charge_times.start(),
+            charge_times.end(),
+            self.get_number_of_winners(),
+            self.get_optimal_charge_time(),
+            self.get_max_distance(),
+        ));
+        if self.get_number_of_winners() <= max_listed {
+            for ct in charge_times {
+                breakdown.push_str(&format!(
+                    "  {} ms: {} mm (+{})\n",
+                    ct,
+                    self.get_distance(ct),
+                    self.get_margin(ct)
+                ));
+            }
+        }
+        breakdown
     }
 }
 
@@ -71,11 +152,93 @@ fn part2(input: &Race) -> usize {
     input.get_number_of_winners()
 }
 
+#[aoc(day6, part1, Breakdown)]
+fn part1_breakdown(input: &[Race]) -> String {
+    let breakdowns: String = input.iter().map(|r| r.get_breakdown(100)).collect();
+    format!("{}\n{}", part1(input), breakdowns)
+}
+
+#[aoc(day6, part2, Breakdown)]
+fn part2_breakdown(input: &Race) -> String {
+    format!("{}\n{}", part2(input), input.get_breakdown(100))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
 
+    #[test]
+    fn test_get_number_of_winners() {
+        // Compare with a brute force calculation for small races.
+        for time in 0..60 {
+            for distance in 0..time * time / 4 + 2 {
+                let race = Race { time, distance };
+                let expected = (0..=time).filter(|ct| ct * (time - ct) > distance).count();
+                assert_eq!(expected, race.get_number_of_winners(), "{:?}", race);
+            }
+        }
+
+        // Races in which the record is reached exactly by a whole charge time.
+        let race = Race {
+            time: 10,
+            distance: 24,
+        };
+        assert_eq!(1, race.get_number_of_winners());
+        let race = Race {
+            time: 10,
+            distance: 25,
+        };
+        assert_eq!(0, race.get_number_of_winners());
+
+        // Large races which lose precision when using floating point numbers.
+        let tests = [
+            (10_000_000_000_000_000, 1),
+            (10_000_000_000_000_000, 1_000),
+            (4_000_000_000, 123_456_789),
+            (4_000_000_001, 1_999_999_999),
+        ];
+        for (time, ct) in tests {
+            let race = Race {
+                time,
+                distance: ct * (time - ct),
+            };
+            assert_eq!(time - 2 * ct - 1, race.get_number_of_winners());
+        }
+    }
+
+    #[test]
+    fn test_strategy() {
+        let race = Race {
+            time: 7,
+            distance: 9,
+        };
+        assert_eq!(Some(2..=5), race.get_winning_charge_times());
+        assert_eq!(3, race.get_optimal_charge_time());
+        assert_eq!(12, race.get_max_distance());
+        let margins: Vec<i128> = (0..=7).map(|ct| race.get_margin(ct)).collect();
+        assert_eq!(vec![-9, -3, 1, 3, 3, 1, -3, -9], margins);
+
+        let expected = indoc! {"
+            Race of 7 ms with a record of 9 mm: charge for 2 to 5 ms to win in 4 ways, the optimal charge time of 3 ms reaches 12 mm.
+              2 ms: 10 mm (+1)
+              3 ms: 12 mm (+3)
+              4 ms: 12 mm (+3)
+              5 ms: 10 mm (+1)
+        "};
+        assert_eq!(expected, race.get_breakdown(4));
+
+        let race = Race {
+            time: 4,
+            distance: 4,
+        };
+        assert_eq!(None, race.get_winning_charge_times());
+        assert_eq!(
+            "Race of 4 ms with a record of 4 mm: the record can't be beaten.\n",
+            race.get_breakdown(4)
+        );
+    }
+
     #[test]
     fn test_parse_input_part1() {
         let expected = vec![
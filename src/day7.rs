@@ -1,4 +1,5 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 
@@ -6,22 +7,41 @@ use std::collections::HashMap;
 struct Hand {
     cards: String,
     bid: usize,
-    joker: bool,
+    /// The rank of the hand type followed by the value of each card. Hands are sorted by this key,
+    /// so it is calculated once when the hand is dealt.
+    sort_key: (usize, Vec<usize>),
 }
 
-impl<T> From<T> for Hand
-where
-    T: Into<String>,
-{
-    fn from(line: T) -> Self {
-        let line = line.into();
-        let cards = line[..5].to_string();
-        let bid = line[6..].trim().parse().unwrap();
-        Hand {
-            cards,
-            bid,
-            joker: false,
+impl Hand {
+    /// Deals a hand from a line containing the cards and the bid. Returns an error if the line is
+    /// malformed, or if the cards don't fit the ruleset.
+    fn new(line: &str, ruleset: &Ruleset) -> Result<Self, String> {
+        let mut parts = line.split_whitespace();
+        let (Some(cards), Some(bid), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(format!("Expected cards and a bid, got '{}'.", line));
+        };
+        let bid = bid.parse().map_err(|_| format!("Invalid bid '{}'.", bid))?;
+        if let Some(hand_size) = ruleset.hand_size {
+            if cards.chars().count() != hand_size {
+                return Err(format!("Hand {} doesn't have {} cards.", cards, hand_size));
+            }
         }
+        if let Some(card) = cards
+            .chars()
+            .find(|c| !ruleset.card_order.contains(c) && !ruleset.wildcards.contains(c))
+        {
+            return Err(format!("Unknown card '{}' in hand {}.", card, cards));
+        }
+        let values = cards.chars().map(|c| ruleset.get_card_value(c)).collect();
+        let rank = ruleset
+            .get_type_rank(cards)
+            .ok_or(format!("Hand {} doesn't match any hand type.", cards))?;
+        let sort_key = (rank, values);
+        Ok(Hand {
+            cards: cards.to_string(),
+            bid,
+            sort_key,
+        })
     }
 }
 
@@ -33,135 +53,191 @@ impl PartialOrd<Self> for Hand {
 
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let t1: Type = (*self).clone().into();
-        let t2: Type = (*other).clone().into();
-        if t1 > t2 {
-            return Ordering::Greater;
-        }
-        if t1 < t2 {
-            return Ordering::Less;
+        // Compare the types first. If they are the same, compare the cards starting from the first.
+        self.sort_key.cmp(&other.sort_key)
+    }
+}
+
+/// A type of hand, defined by the groups of identical cards it needs to contain. For example a
+/// full house needs a group of 3 and a group of 2 cards.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct HandType {
+    name: String,
+    /// The sizes of the groups, from large to small.
+    groups: Vec<usize>,
+}
+
+impl HandType {
+    fn new(name: &str, groups: &[usize]) -> Self {
+        let mut groups = groups.to_vec();
+        groups.sort_by(|a, b| b.cmp(a));
+        HandType {
+            name: name.to_string(),
+            groups,
         }
-        // Types are the same, compare the cards starting from the first.
-        let hand_1_chars = self.cards.chars().collect::<Vec<_>>();
-        let hand_2_chars = other.cards.chars().collect::<Vec<_>>();
-        for i in 0..5 {
-            let v1 = get_card_value(hand_1_chars[i], self.joker);
-            let v2 = get_card_value(hand_2_chars[i], other.joker);
-            match v1.cmp(&v2) {
-                Ordering::Greater => return Ordering::Greater,
-                Ordering::Less => return Ordering::Less,
-                _ => (),
+    }
+
+    /// Returns all possible hand types for hands of the given size, from lowest to highest. Every
+    /// way to divide the cards into groups is a hand type, and hands with larger groups win. For
+    /// hands of 5 cards this results in the regular Camel Cards hand types.
+    fn for_hand_size(hand_size: usize) -> Vec<HandType> {
+        // Returns all ways to divide the given number of cards into groups of at most `max` cards.
+        fn get_partitions(cards: usize, max: usize) -> Vec<Vec<usize>> {
+            if cards == 0 {
+                return vec![vec![]];
             }
+            (1..=cards.min(max))
+                .flat_map(|size| {
+                    get_partitions(cards - size, size)
+                        .into_iter()
+                        .map(move |mut partition| {
+                            partition.insert(0, size);
+                            partition
+                        })
+                })
+                .collect()
         }
-        Ordering::Equal
+
+        let mut partitions = get_partitions(hand_size, hand_size);
+        partitions.sort();
+        partitions
+            .iter()
+            .map(|partition| {
+                let name = partition.iter().map(|g| g.to_string()).join("-");
+                // Groups of a single card don't need to be matched.
+                let groups: Vec<usize> = partition.iter().copied().filter(|g| *g > 1).collect();
+                HandType::new(&name, &groups)
+            })
+            .collect()
+    }
+
+    /// Returns how many cards are missing to form this hand type from a hand with the given group
+    /// sizes, sorted from large to small.
+    fn get_missing_cards(&self, groups: &[usize]) -> usize {
+        self.groups
+            .iter()
+            .zip(groups.iter().chain(std::iter::repeat(&0)))
+            .map(|(required, available)| required.saturating_sub(*available))
+            .sum()
     }
 }
 
-#[derive(Debug, PartialEq, Ord, PartialOrd, Eq, Clone, Copy)]
-enum Type {
-    FiveOfAKind = 6,
-    FourOfAKind = 5,
-    FullHouse = 4,
-    ThreeOfAKind = 3,
-    TwoPairs = 2,
-    OnePair = 1,
-    HighCard = 0,
+/// The rules of the card game: the value of the cards, which cards are wild and which types of
+/// hands exist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Ruleset {
+    /// The cards, from lowest to highest value.
+    card_order: Vec<char>,
+    /// Cards that can stand in for any other card. They have the lowest value of all cards.
+    wildcards: Vec<char>,
+    /// The types of hands, from lowest to highest.
+    hand_types: Vec<HandType>,
+    /// The number of cards in a hand, or `None` if hands can have any number of cards.
+    hand_size: Option<usize>,
 }
 
-impl<T> From<T> for Type
-where
-    T: Into<Hand>,
-{
-    fn from(hand: T) -> Self {
-        let hand = hand.into();
-        if !hand.joker {
-            return get_type_for_hand(&hand.cards);
-        }
-        // If we have one or more jokers, substitute them for each card in the deck and get the
-        // type for each hand.
-        let mut highest_type = Type::HighCard;
-        // Retrieve the cards in the hand, and remove duplicates.
-        let mut held_cards: Vec<char> = hand.cards.chars().collect();
-        held_cards.sort();
-        held_cards.dedup();
-        // Get a version of the hand with the jokers removed.
-        let cards_with_jokers_removed =
-            hand.cards.chars().filter(|c| *c != 'J').collect::<String>();
-
-        // If there are no jokers, return the type of the hand.
-        if cards_with_jokers_removed.len() == 5 {
-            return get_type_for_hand(&hand.cards);
+impl Ruleset {
+    fn new(
+        card_order: &str,
+        wildcards: &str,
+        hand_types: Vec<HandType>,
+        hand_size: Option<usize>,
+    ) -> Self {
+        Ruleset {
+            card_order: card_order.chars().collect(),
+            wildcards: wildcards.chars().collect(),
+            hand_types,
+            hand_size,
         }
+    }
 
-        held_cards.iter().for_each(|c| {
-            // If we already have a FiveOfAKind, we don't need to check any more.
-            if highest_type == Type::FiveOfAKind {
-                return;
-            }
-            let mut cards = cards_with_jokers_removed.clone();
-            // Fill up to 5 cards.
-            while cards.len() < 5 {
-                cards.push(*c);
-            }
-            let t = get_type_for_hand(&cards);
-            if t > highest_type {
-                highest_type = t;
-            }
-        });
+    /// Returns the rules of Camel Cards for hands of the given size. The regular game is played
+    /// with 5 cards.
+    fn camel_cards(hand_size: usize) -> Self {
+        let hand_types = match hand_size {
+            5 => vec![
+                HandType::new("High card", &[]),
+                HandType::new("One pair", &[2]),
+                HandType::new("Two pairs", &[2, 2]),
+                HandType::new("Three of a kind", &[3]),
+                HandType::new("Full house", &[3, 2]),
+                HandType::new("Four of a kind", &[4]),
+                HandType::new("Five of a kind", &[5]),
+            ],
+            _ => HandType::for_hand_size(hand_size),
+        };
+        Ruleset::new("23456789TJQKA", "", hand_types, Some(hand_size))
+    }
 
-        highest_type
+    /// Returns the rules of Camel Cards, played with jokers.
+    fn camel_cards_with_jokers(hand_size: usize) -> Self {
+        Ruleset {
+            wildcards: vec!['J'],
+            ..Ruleset::camel_cards(hand_size)
+        }
     }
-}
 
-fn get_type_for_hand(hand: &str) -> Type {
-    let mut cards: HashMap<char, usize> = HashMap::new();
-    hand.chars().for_each(|c| {
-        *cards.entry(c).or_insert(0) += 1;
-    });
-    let mut values: Vec<usize> = cards.values().copied().collect();
-    values.sort();
-    match values.as_slice() {
-        [1, 1, 1, 1, 1] => Type::HighCard,
-        [1, 1, 1, 2] => Type::OnePair,
-        [1, 2, 2] => Type::TwoPairs,
-        [1, 1, 3] => Type::ThreeOfAKind,
-        [2, 3] => Type::FullHouse,
-        [1, 4] => Type::FourOfAKind,
-        [5] => Type::FiveOfAKind,
-        _ => unreachable!(),
+    fn get_card_value(&self, card: char) -> usize {
+        if self.wildcards.contains(&card) {
+            return 0;
+        }
+        self.card_order.iter().position(|c| *c == card).unwrap() + 1
     }
-}
 
-fn get_card_value(card: char, joker: bool) -> usize {
-    match card {
-        'A' => 14,
-        'K' => 13,
-        'Q' => 12,
-        'J' => match joker {
-            true => 0,
-            false => 11,
-        },
-        'T' => 10,
-        _ => card.to_digit(10).unwrap_or(0) as usize,
+    /// Returns the rank of the type of the given hand. Wildcards take the value that results in
+    /// the highest type. Returns `None` if the hand doesn't match any of the hand types.
+    fn get_type_rank(&self, cards: &str) -> Option<usize> {
+        // Count the identical cards, keeping the wildcards apart.
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        let mut wildcards = 0;
+        cards
+            .chars()
+            .for_each(|c| match self.wildcards.contains(&c) {
+                true => wildcards += 1,
+                false => *counts.entry(c).or_insert(0) += 1,
+            });
+        let mut groups: Vec<usize> = counts.values().copied().collect();
+        groups.sort_by(|a, b| b.cmp(a));
+
+        // Wildcards can complete any group, so we can form every hand type for which we have
+        // enough wildcards to fill in the missing cards. For the standard hand types this comes
+        // down to adding all wildcards to the largest group.
+        self.hand_types
+            .iter()
+            .rposition(|hand_type| hand_type.get_missing_cards(&groups) <= wildcards)
     }
 }
-#[aoc_generator(day7, part1)]
-fn parse_input_part1(input: &str) -> Vec<Hand> {
-    input.lines().map(|line| line.into()).collect()
+
+/// Returns the number of cards in the hands of the given input.
+fn get_hand_size(input: &str) -> usize {
+    input
+        .split_whitespace()
+        .next()
+        .map_or(0, |cards| cards.chars().count())
 }
 
-#[aoc_generator(day7, part2)]
-fn parse_input_part2(input: &str) -> Vec<Hand> {
+/// Deals a hand for every line of the input, reporting the line number of a malformed hand.
+fn parse_hands(input: &str, ruleset: &Ruleset) -> Result<Vec<Hand>, String> {
     input
         .lines()
-        .map(|line| line.into())
-        .map(|mut hand: Hand| {
-            hand.joker = true;
-            hand
-        })
+        .enumerate()
+        .map(|(i, line)| Hand::new(line, ruleset).map_err(|e| format!("Line {}: {}", i + 1, e)))
         .collect()
 }
 
+#[aoc_generator(day7, part1)]
+fn parse_input_part1(input: &str) -> Result<Vec<Hand>, String> {
+    parse_hands(input, &Ruleset::camel_cards(get_hand_size(input)))
+}
+
+#[aoc_generator(day7, part2)]
+fn parse_input_part2(input: &str) -> Result<Vec<Hand>, String> {
+    parse_hands(
+        input,
+        &Ruleset::camel_cards_with_jokers(get_hand_size(input)),
+    )
+}
+
 #[aoc(day7, part1)]
 fn part1(input: &[Hand]) -> usize {
     let mut input = input.to_vec();
@@ -186,21 +262,27 @@ mod tests {
     #[test]
     fn test_card_into_type() {
         let tests = vec![
-            ("32T4K", Type::HighCard),
-            ("T5QJ5", Type::OnePair),
-            ("KK677", Type::TwoPairs),
-            ("KTJJJ", Type::ThreeOfAKind),
-            ("QQQAA", Type::FullHouse),
-            ("42444", Type::FourOfAKind),
-            ("AAAAA", Type::FiveOfAKind),
+            ("32T4K", "High card", "High card"),
+            ("32TJK", "High card", "One pair"),
+            ("T5QJ5", "One pair", "Three of a kind"),
+            ("KK677", "Two pairs", "Two pairs"),
+            ("KTJJJ", "Three of a kind", "Four of a kind"),
+            ("QQQAA", "Full house", "Full house"),
+            ("QQJAA", "Two pairs", "Full house"),
+            ("42444", "Four of a kind", "Four of a kind"),
+            ("AAAAA", "Five of a kind", "Five of a kind"),
+            ("JJJJJ", "Five of a kind", "Five of a kind"),
         ];
-        for (cards, expected) in tests {
-            let hand = Hand {
-                cards: cards.to_string(),
-                bid: 0,
-                joker: false,
-            };
-            assert_eq!(expected, Type::from(hand));
+        let ruleset = Ruleset::camel_cards(5);
+        let ruleset_with_jokers = Ruleset::camel_cards_with_jokers(5);
+        for (cards, expected, expected_with_jokers) in tests {
+            let rank = ruleset.get_type_rank(cards).unwrap();
+            assert_eq!(expected, ruleset.hand_types[rank].name);
+            let rank = ruleset_with_jokers.get_type_rank(cards).unwrap();
+            assert_eq!(
+                expected_with_jokers,
+                ruleset_with_jokers.hand_types[rank].name
+            );
         }
     }
 
@@ -227,52 +309,170 @@ mod tests {
             ("KK677", "KK6AA", Ordering::Less),
             ("KK699", "KK6TT", Ordering::Less),
         ];
+        let ruleset = Ruleset::camel_cards(5);
         for (cards1, cards2, expected) in tests {
-            let hand1 = Hand {
-                cards: cards1.to_string(),
-                bid: 0,
-                joker: false,
-            };
-            let hand2 = Hand {
-                cards: cards2.to_string(),
-                bid: 0,
-                joker: false,
-            };
+            let hand1 = Hand::new(&format!("{} 0", cards1), &ruleset).unwrap();
+            let hand2 = Hand::new(&format!("{} 0", cards2), &ruleset).unwrap();
             assert_eq!(expected, hand1.cmp(&hand2));
         }
     }
 
+    #[test]
+    fn test_wildcards() {
+        // Compare the analytic wildcard resolution with substituting the wildcards by every
+        // possible card.
+        let ruleset = Ruleset::camel_cards(5);
+        let ruleset_with_jokers = Ruleset::camel_cards_with_jokers(5);
+        let cards = ['2', '3', '4', 'J', 'A'];
+        for hand in (0..5).map(|_| cards.iter()).multi_cartesian_product() {
+            let hand: String = hand.into_iter().collect();
+            let expected = cards
+                .iter()
+                .map(|c| {
+                    ruleset
+                        .get_type_rank(&hand.replace('J', &c.to_string()))
+                        .unwrap()
+                })
+                .max()
+                .unwrap();
+            assert_eq!(
+                expected,
+                ruleset_with_jokers.get_type_rank(&hand).unwrap(),
+                "{}",
+                hand
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_ruleset() {
+        // A variant in which aces are low, twos are wild and full houses don't exist.
+        let hand_types = Ruleset::camel_cards(5)
+            .hand_types
+            .into_iter()
+            .filter(|hand_type| hand_type.name != "Full house")
+            .collect();
+        let ruleset = Ruleset::new("A3456789TJQK", "2", hand_types, Some(5));
+
+        let hand = |cards: &str| Hand::new(&format!("{} 0", cards), &ruleset).unwrap();
+        assert_eq!(Ordering::Less, hand("AKQJT").cmp(&hand("3KQJT")));
+        assert_eq!(Ordering::Greater, hand("AAKQJ").cmp(&hand("2AKQJ")));
+        assert_eq!(Ordering::Greater, hand("KKKK2").cmp(&hand("AAAAA")));
+        assert_eq!(Ordering::Less, hand("QQQAA").cmp(&hand("KKKA3")));
+        assert_eq!(
+            "Three of a kind",
+            ruleset.hand_types[ruleset.get_type_rank("QQ2AA").unwrap()].name
+        );
+        // Hands that don't match any hand type of the ruleset can't be dealt.
+        let ruleset = Ruleset::new("23456789TJQKA", "", vec![HandType::new("Pair", &[2])], None);
+        assert_eq!(None, ruleset.get_type_rank("23456"));
+        assert_eq!(
+            Err("Hand 23456 doesn't match any hand type.".to_string()),
+            Hand::new("23456 1", &ruleset)
+        );
+        assert!(Hand::new("23446 1", &ruleset).is_ok());
+    }
+
+    #[test]
+    fn test_hand_size() {
+        // For hands of 5 cards the generated hand types match the regular ones.
+        let groups: Vec<Vec<usize>> = HandType::for_hand_size(5)
+            .into_iter()
+            .map(|hand_type| hand_type.groups)
+            .collect();
+        let expected: Vec<Vec<usize>> = Ruleset::camel_cards(5)
+            .hand_types
+            .into_iter()
+            .map(|hand_type| hand_type.groups)
+            .collect();
+        assert_eq!(expected, groups);
+
+        // Hands of 7 cards drawn from multiple decks.
+        let ruleset = Ruleset::camel_cards_with_jokers(7);
+        assert_eq!(15, ruleset.hand_types.len());
+        let tests = vec![
+            ("2345678", "1-1-1-1-1-1-1"),
+            ("2233445", "2-2-2-1"),
+            ("222333A", "3-3-1"),
+            ("22233JA", "4-2-1"),
+            ("2222333", "4-3"),
+            ("AAAAAAA", "7"),
+            ("AAJJAAA", "7"),
+        ];
+        for (cards, expected) in tests {
+            let rank = ruleset.get_type_rank(cards).unwrap();
+            assert_eq!(expected, ruleset.hand_types[rank].name);
+        }
+
+        // The hand size is taken from the input.
+        let input = indoc! {"
+            KKK 5
+            A2A 20
+            J23 1
+            T3T 7
+            JJ2 3
+        "};
+        let hands = parse_input_part1(input).unwrap();
+        assert_eq!(1 + 2 * 7 + 3 * 3 + 4 * 20 + 5 * 5, part1(&hands));
+        let hands = parse_input_part2(input).unwrap();
+        assert_eq!(1 + 2 * 7 + 3 * 20 + 4 * 3 + 5 * 5, part2(&hands));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let tests = [
+            ("32T3K\n", "Line 1: Expected cards and a bid, got '32T3K'."),
+            (
+                "32T3K 765 1\n",
+                "Line 1: Expected cards and a bid, got '32T3K 765 1'.",
+            ),
+            ("32T3K abc\n", "Line 1: Invalid bid 'abc'."),
+            (
+                "32T3K 765\nT55J 684\n",
+                "Line 2: Hand T55J doesn't have 5 cards.",
+            ),
+            (
+                "32T3K 765\nT55X5 684\n",
+                "Line 2: Unknown card 'X' in hand T55X5.",
+            ),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(Err(expected.to_string()), parse_input_part1(input));
+            assert_eq!(Err(expected.to_string()), parse_input_part2(input));
+        }
+    }
+
     #[test]
     fn test_parse_input_part1() {
         let expected = vec![
             Hand {
                 cards: "32T3K".to_string(),
                 bid: 765,
-                joker: false,
+                sort_key: (1, vec![2, 1, 9, 2, 12]),
             },
             Hand {
                 cards: "T55J5".to_string(),
                 bid: 684,
-                joker: false,
+                sort_key: (3, vec![9, 4, 4, 10, 4]),
             },
             Hand {
                 cards: "KK677".to_string(),
                 bid: 28,
-                joker: false,
+                sort_key: (2, vec![12, 12, 5, 6, 6]),
             },
             Hand {
                 cards: "KTJJT".to_string(),
                 bid: 220,
-                joker: false,
+                sort_key: (2, vec![12, 9, 10, 10, 9]),
             },
             Hand {
                 cards: "QQQJA".to_string(),
                 bid: 483,
-                joker: false,
+                sort_key: (3, vec![11, 11, 11, 10, 13]),
             },
         ];
 
-        assert_eq!(expected, parse_input_part1(get_test_input_part1()));
+        assert_eq!(expected, parse_input_part1(get_test_input_part1()).unwrap());
     }
 
     #[test]
@@ -281,42 +481,42 @@ mod tests {
             Hand {
                 cards: "32T3K".to_string(),
                 bid: 765,
-                joker: true,
+                sort_key: (1, vec![2, 1, 9, 2, 12]),
             },
             Hand {
                 cards: "T55J5".to_string(),
                 bid: 684,
-                joker: true,
+                sort_key: (5, vec![9, 4, 4, 0, 4]),
             },
             Hand {
                 cards: "KK677".to_string(),
                 bid: 28,
-                joker: true,
+                sort_key: (2, vec![12, 12, 5, 6, 6]),
             },
             Hand {
                 cards: "KTJJT".to_string(),
                 bid: 220,
-                joker: true,
+                sort_key: (5, vec![12, 9, 0, 0, 9]),
             },
             Hand {
                 cards: "QQQJA".to_string(),
                 bid: 483,
-                joker: true,
+                sort_key: (5, vec![11, 11, 11, 0, 13]),
             },
         ];
 
-        assert_eq!(expected, parse_input_part2(get_test_input_part2()));
+        assert_eq!(expected, parse_input_part2(get_test_input_part2()).unwrap());
     }
 
     #[test]
     fn part1_example() {
-        let input = parse_input_part1(get_test_input_part1());
+        let input = parse_input_part1(get_test_input_part1()).unwrap();
         assert_eq!(6440, part1(&input));
     }
 
     #[test]
     fn part2_example() {
-        let input = parse_input_part2(get_test_input_part2());
+        let input = parse_input_part2(get_test_input_part2()).unwrap();
         assert_eq!(5905, part2(&input));
     }
 
@@ -1,98 +1,495 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use num_integer::Integer;
+use itertools::Itertools;
+use num_integer::{ExtendedGcd, Integer};
+use regex::Regex;
 use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// The network of nodes. The node ids are interned: each node is identified by an index, and the
+/// children of the nodes are kept in an adjacency table so walking the network is cheap.
+#[derive(Debug, Default, PartialEq)]
+struct Network {
+    /// The directions to follow, 0 for left and 1 for right.
+    directions: Vec<usize>,
+    /// The ids of the nodes, by index.
+    ids: Vec<String>,
+    /// The indexes of the nodes, by id.
+    indexes: HashMap<String, u32>,
+    /// The left and right children of each node, by index.
+    children: Vec<[u32; 2]>,
+    /// The indexes of the nodes, in the order in which they are defined.
+    definitions: Vec<u32>,
+}
+
+impl Network {
+    /// Returns the index of the node with the given id, adding the node if it is not yet known. A
+    /// new node leads to itself until it is defined.
+    fn intern(&mut self, id: &str) -> u32 {
+        if let Some(index) = self.indexes.get(id) {
+            return *index;
+        }
+        let index = self.ids.len() as u32;
+        self.ids.push(id.to_string());
+        self.indexes.insert(id.to_string(), index);
+        self.children.push([index, index]);
+        index
+    }
+
+    fn get_index(&self, id: &str) -> Option<u32> {
+        self.indexes.get(id).copied()
+    }
+
+    fn get_id(&self, index: u32) -> &str {
+        &self.ids[index as usize]
+    }
+
+    /// Returns the node that is reached from the given node in the given step of the walk.
+    fn get_child(&self, node: u32, step: usize) -> u32 {
+        self.children[node as usize][self.directions[step % self.directions.len()]]
+    }
+
+    /// Returns for each node whether it matches the given matcher, by index.
+    fn get_matching(&self, matcher: &NodeMatcher) -> Vec<bool> {
+        self.ids.iter().map(|id| matcher.matches(id)).collect()
+    }
+
+    /// Returns the indexes of the nodes that match the given matcher. At least one node should
+    /// match.
+    fn find_nodes(&self, matcher: &NodeMatcher) -> Result<Vec<u32>, WalkError> {
+        let nodes: Vec<u32> = match matcher {
+            NodeMatcher::Id(id) => self.get_index(id).into_iter().collect(),
+            _ => (0..self.ids.len() as u32)
+                .filter(|node| matcher.matches(self.get_id(*node)))
+                .collect(),
+        };
+        match nodes.is_empty() {
+            true => Err(WalkError::NoStartNodes(matcher.to_string())),
+            false => Ok(nodes),
+        }
+    }
+
+    /// Returns the nodes that are visited when walking from the start node to a goal node. Returns
+    /// an error if the goal can't be reached, or is not reached within the step limit.
+    fn walk(
+        &self,
+        start: u32,
+        goal: &NodeMatcher,
+        step_limit: Option<usize>,
+    ) -> Result<Vec<u32>, WalkError> {
+        let is_goal = self.get_matching(goal);
+        // Keep track of the visited states (node, position in the directions). If we visit a state
+        // for the second time we are going around in circles.
+        let len = self.directions.len();
+        let mut visited = vec![false; self.ids.len() * len];
+        let mut path = vec![start];
+        let mut current_node = start;
+        while !is_goal[current_node as usize] {
+            let steps = path.len() - 1;
+            if step_limit.is_some_and(|limit| steps >= limit) {
+                let start_id = self.get_id(start).to_string();
+                return Err(WalkError::StepLimitReached(start_id, steps));
+            }
+            let state = current_node as usize * len + steps % len;
+            if visited[state] {
+                let start_id = self.get_id(start).to_string();
+                return Err(WalkError::GoalUnreachable(start_id));
+            }
+            visited[state] = true;
+            current_node = self.get_child(current_node, steps);
+            path.push(current_node);
+        }
+        Ok(path)
+    }
+
+    /// Returns the network as a graph in the Graphviz DOT language. Start and end nodes are
+    /// highlighted, and the edges taken by each of the given paths are coloured.
+    fn to_dot(&self, paths: &[Vec<u32>]) -> String {
+        const COLORS: [&str; 6] = ["red", "blue", "darkgreen", "orange", "purple", "brown"];
+
+        // Find the colour of the edges that are taken, keyed by node and direction.
+        let mut edge_colors: HashMap<(u32, usize), &str> = HashMap::new();
+        for (i, path) in paths.iter().enumerate() {
+            for (step, node) in path.iter().enumerate().take(path.len().saturating_sub(1)) {
+                let direction = self.directions[step % self.directions.len()];
+                edge_colors
+                    .entry((*node, direction))
+                    .or_insert(COLORS[i % COLORS.len()]);
+            }
+        }
+
+        let mut dot = String::from("digraph network {\n");
+        for (index, id) in self.ids.iter().enumerate() {
+            let style = match id {
+                _ if id.ends_with('A') => " [style=filled, fillcolor=palegreen]",
+                _ if id.ends_with('Z') => " [style=filled, fillcolor=lightpink]",
+                _ => "",
+            };
+            dot.push_str(&format!("    \"{}\"{};\n", id, style));
+
+            // Draw a single edge if both directions lead to the same node.
+            let [left, right] = self.children[index];
+            let edges = match left == right {
+                true => vec![(left, "LR", vec![0, 1])],
+                false => vec![(left, "L", vec![0]), (right, "R", vec![1])],
+            };
+            for (child, label, directions) in edges {
+                let color = directions
+                    .iter()
+                    .find_map(|d| edge_colors.get(&(index as u32, *d)));
+                let color = color.map_or(String::new(), |c| format!(", color={}, penwidth=2", c));
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"{}];\n",
+                    id,
+                    self.get_id(child),
+                    label,
+                    color
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Writes the network as a Graphviz DOT file with the given paths highlighted.
+fn export_dot(network: &Network, paths: &[Vec<u32>], path: &Path) -> Result<(), String> {
+    fs::write(path, network.to_dot(paths))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Selects nodes by their id.
+#[derive(Clone, Debug)]
+enum NodeMatcher {
+    /// Matches the node with exactly this id.
+    Id(String),
+    /// Matches the nodes with an id that ends with this suffix.
+    Suffix(String),
+    /// Matches the nodes with an id that matches this regular expression.
+    Regex(Regex),
+}
+
+impl NodeMatcher {
+    fn matches(&self, id: &str) -> bool {
+        match self {
+            NodeMatcher::Id(i) => id == i,
+            NodeMatcher::Suffix(suffix) => id.ends_with(suffix.as_str()),
+            NodeMatcher::Regex(re) => re.is_match(id),
+        }
+    }
+}
+
+impl std::fmt::Display for NodeMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeMatcher::Id(id) => write!(f, "id {}", id),
+            NodeMatcher::Suffix(suffix) => write!(f, "suffix {}", suffix),
+            NodeMatcher::Regex(re) => write!(f, "pattern {}", re),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
-struct Node {
-    id: String,
-    children: [String; 2],
+enum WalkError {
+    /// No nodes match the start matcher.
+    NoStartNodes(String),
+    /// The walk from the given node goes around in circles without ever reaching a goal.
+    GoalUnreachable(String),
+    /// The walk from the given node did not reach a goal within the given number of steps.
+    StepLimitReached(String, usize),
+    /// The walks never reach their goals at the same time.
+    NoCommonGoal,
 }
 
-impl Node {
-    fn get_child_id_by_direction(&self, d: &char) -> String {
-        match d {
-            'L' => self.children[0].clone(),
-            'R' => self.children[1].clone(),
-            _ => unreachable!(),
+impl std::fmt::Display for WalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalkError::NoStartNodes(matcher) => write!(f, "No nodes match {}.", matcher),
+            WalkError::GoalUnreachable(id) => {
+                write!(f, "The goal can't be reached from {}.", id)
+            }
+            WalkError::StepLimitReached(id, steps) => {
+                write!(f, "The goal is not reached from {} in {} steps.", id, steps)
+            }
+            WalkError::NoCommonGoal => {
+                write!(f, "The walks never reach their goals at the same time.")
+            }
         }
     }
 }
 
+impl Error for WalkError {}
+
+// A display formatter that outputs the network in the same format as the input, with the nodes in
+// the order in which they were defined.
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let directions: String = self
+            .directions
+            .iter()
+            .map(|d| if *d == 0 { 'L' } else { 'R' })
+            .collect();
+        writeln!(f, "{}\n", directions)?;
+        for index in &self.definitions {
+            let [left, right] = self.children[*index as usize];
+            writeln!(
+                f,
+                "{} = ({}, {})",
+                self.get_id(*index),
+                self.get_id(left),
+                self.get_id(right)
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[aoc_generator(day8, part1)]
-fn parse_input_part1(input: &str) -> (String, HashMap<String, Node>) {
+fn parse_input_part1(input: &str) -> Result<Network, String> {
     let mut lines = input.lines();
 
     // The first line contains the directions.
-    let directions = lines.next().unwrap().to_string();
+    let directions: Vec<usize> = lines
+        .next()
+        .ok_or("The input is empty.")?
+        .chars()
+        .map(|d| match d {
+            'L' => Ok(0),
+            'R' => Ok(1),
+            _ => Err(format!("Invalid direction '{}'.", d)),
+        })
+        .collect::<Result<_, _>>()?;
+    if directions.is_empty() {
+        return Err("There are no directions.".to_string());
+    }
 
     // Skip the empty line.
     lines.next();
 
-    let re = regex::Regex::new(r"(\w+) = \((\w+), (\w+)\)").unwrap();
-    let nodes: HashMap<String, Node> = lines
-        .map(|line| {
-            let caps = re.captures(line).unwrap();
-            let id = caps[1].to_string();
-            let children = [caps[2].to_string(), caps[3].to_string()];
-            (id.clone(), Node { id, children })
-        })
-        .collect();
+    let mut network = Network {
+        directions,
+        ..Default::default()
+    };
+    let re = Regex::new(r"(\w+) = \((\w+), (\w+)\)").unwrap();
+    for line in lines {
+        let caps = re
+            .captures(line)
+            .ok_or(format!("Invalid node '{}'.", line))?;
+        let index = network.intern(&caps[1]);
+        let children = [network.intern(&caps[2]), network.intern(&caps[3])];
+        network.children[index as usize] = children;
+        network.definitions.push(index);
+    }
+
+    // Every node that is referenced should be defined exactly once.
+    let mut defined = vec![false; network.ids.len()];
+    for index in &network.definitions {
+        if std::mem::replace(&mut defined[*index as usize], true) {
+            let id = network.get_id(*index);
+            return Err(format!("Node {} is defined more than once.", id));
+        }
+    }
+    if let Some(index) = defined.iter().position(|defined| !defined) {
+        return Err(format!("Node {} is not defined.", network.ids[index]));
+    }
 
-    (directions, nodes)
+    Ok(network)
 }
 
 #[aoc_generator(day8, part2)]
-fn parse_input_part2(input: &str) -> (String, HashMap<String, Node>) {
+fn parse_input_part2(input: &str) -> Result<Network, String> {
     parse_input_part1(input)
 }
 
 #[aoc(day8, part1)]
-fn part1(input: &(String, HashMap<String, Node>)) -> usize {
-    let (directions, nodes) = input;
-    let directions: Vec<char> = directions.chars().collect();
+fn part1(network: &Network) -> Result<usize, WalkError> {
+    Ok(get_part1_path(network)?.len() - 1)
+}
+
+/// Walks from the nodes matching the `DAY8_START` pattern to the nodes matching the `DAY8_GOAL`
+/// pattern, and returns the number of steps of the longest walk. The walks can be limited to a
+/// number of steps with `DAY8_STEP_LIMIT`. Defaults to the walk from part 1.
+#[aoc(day8, part1, Custom)]
+fn part1_custom(network: &Network) -> Result<usize, Box<dyn Error>> {
+    let get_matcher = |var: &str, default: &str| -> Result<NodeMatcher, Box<dyn Error>> {
+        let pattern = env::var(var).unwrap_or(default.to_string());
+        Ok(NodeMatcher::Regex(Regex::new(&pattern)?))
+    };
+    let start = get_matcher("DAY8_START", "^AAA$")?;
+    let goal = get_matcher("DAY8_GOAL", "^ZZZ$")?;
+    let step_limit = match env::var("DAY8_STEP_LIMIT") {
+        Ok(limit) => Some(limit.parse::<usize>()?),
+        Err(_) => None,
+    };
+
     let mut steps = 0;
-    let mut current_node = &nodes["AAA"];
-    while current_node.id != "ZZZ" {
-        let current_direction = directions[steps % directions.len()];
-        let next_node_id = current_node.get_child_id_by_direction(&current_direction);
-        current_node = &nodes[&next_node_id];
-        steps += 1;
-    }
-    steps
+    for node in network.find_nodes(&start)? {
+        steps = steps.max(network.walk(node, &goal, step_limit)?.len() - 1);
+    }
+    Ok(steps)
 }
 
-#[aoc(day8, part2)]
-fn part2(input: &(String, HashMap<String, Node>)) -> usize {
-    let (directions, nodes) = input;
-    let directions: Vec<char> = directions.chars().collect();
-
-    // Find all nodes whose ids end with "A".
-    let current_nodes: Vec<&Node> = nodes
-        .values()
-        .filter(|node| node.id.ends_with('A'))
-        .collect();
+fn get_part1_path(network: &Network) -> Result<Vec<u32>, WalkError> {
+    let start = network.find_nodes(&NodeMatcher::Id("AAA".to_string()))?[0];
+    network.walk(start, &NodeMatcher::Id("ZZZ".to_string()), None)
+}
+
+/// Exports the network with the path taken in part 1 to a Graphviz DOT file, if the `DAY8_DOT_DIR`
+/// environment variable is set to the directory to write it to.
+#[aoc(day8, part1, Graphviz)]
+fn part1_graphviz(network: &Network) -> Result<usize, Box<dyn Error>> {
+    let path = get_part1_path(network)?;
+    if let Ok(dir) = env::var("DAY8_DOT_DIR") {
+        let file = Path::new(&dir).join("day8_part1.dot");
+        export_dot(network, std::slice::from_ref(&path), &file)?;
+    }
+    Ok(path.len() - 1)
+}
 
-    // Retrieve the cycle length for each node.
-    let cycle_lengths: Vec<usize> = current_nodes
-        .iter()
-        .map(|node| {
-            let mut current_node = *node;
-            let mut steps = 0;
-            while !current_node.id.ends_with('Z') {
-                let current_direction = directions[steps % directions.len()];
-                let next_node_id = current_node.get_child_id_by_direction(&current_direction);
-                current_node = &nodes[&next_node_id];
-                steps += 1;
+/// Describes the path of a walk through the network. Since the network and the directions are
+/// finite, every walk eventually repeats itself: it starts with a prefix, followed by a cycle that
+/// repeats forever.
+#[derive(Debug, PartialEq)]
+struct Cycle {
+    /// The number of steps before the cycle starts.
+    prefix_length: usize,
+    /// The number of steps in the cycle.
+    length: usize,
+    /// The steps at which an end node is reached, within the prefix and the first iteration of the
+    /// cycle.
+    end_offsets: Vec<usize>,
+}
+
+impl Cycle {
+    /// Walks from the given start node until a state (node, position in the directions) is visited
+    /// for the second time.
+    fn find(network: &Network, start: u32, is_end: impl Fn(u32) -> bool) -> Cycle {
+        // Keep track of the step at which each state was first visited.
+        let len = network.directions.len();
+        let mut visited: Vec<Option<usize>> = vec![None; network.ids.len() * len];
+        let mut end_offsets = vec![];
+        let mut current_node = start;
+        let mut steps = 0;
+        loop {
+            let state = current_node as usize * len + steps % len;
+            if let Some(prefix_length) = visited[state] {
+                return Cycle {
+                    prefix_length,
+                    length: steps - prefix_length,
+                    end_offsets,
+                };
             }
-            steps
-        })
-        .collect();
+            visited[state] = Some(steps);
+            if is_end(current_node) {
+                end_offsets.push(steps);
+            }
+            current_node = network.get_child(current_node, steps);
+            steps += 1;
+        }
+    }
+}
+
+/// Returns the lowest number of steps after which all walks are on an end node at the same time.
+fn solve_cycles(cycles: &[Cycle]) -> Result<usize, WalkError> {
+    let mut solutions = vec![];
+
+    // An end node in the prefix is only reached once. Check if all other walks are on an end node
+    // at the same time.
+    let is_end = |cycle: &Cycle, steps: usize| {
+        let offset = match steps < cycle.prefix_length {
+            true => steps,
+            false => cycle.prefix_length + (steps - cycle.prefix_length) % cycle.length,
+        };
+        cycle.end_offsets.contains(&offset)
+    };
+    for cycle in cycles {
+        for offset in cycle.end_offsets.iter() {
+            if *offset < cycle.prefix_length && cycles.iter().all(|c| is_end(c, *offset)) {
+                solutions.push(*offset);
+            }
+        }
+    }
+
+    // End nodes in the cycle are reached periodically. Every combination of end nodes gives a
+    // system of congruences that can be solved with the Chinese remainder theorem.
+    let min_steps = cycles.iter().map(|c| c.prefix_length).max().unwrap_or(0);
+    let periodic_offsets = cycles.iter().map(|cycle| {
+        cycle
+            .end_offsets
+            .iter()
+            .filter(|offset| **offset >= cycle.prefix_length)
+            .map(|offset| (*offset as i128, cycle.length as i128))
+            .collect::<Vec<_>>()
+    });
+    for congruences in periodic_offsets.multi_cartesian_product() {
+        let solution = congruences
+            .into_iter()
+            .try_fold((0, 1), |(a1, m1), (a2, m2)| {
+                solve_congruences(a1, m1, a2, m2)
+            });
+        if let Some((steps, modulus)) = solution {
+            // The congruences only hold once every walk has entered its cycle.
+            let mut steps = steps as usize;
+            if steps < min_steps {
+                steps += (min_steps - steps).div_ceil(modulus as usize) * modulus as usize;
+            }
+            solutions.push(steps);
+        }
+    }
 
-    // Calculate the lowest common multiple of the cycle lengths.
-    cycle_lengths
+    solutions.into_iter().min().ok_or(WalkError::NoCommonGoal)
+}
+
+/// Solves the system x = a1 (mod m1), x = a2 (mod m2). Returns the lowest non-negative solution
+/// together with the modulus of all solutions, or `None` if there is no solution.
+fn solve_congruences(a1: i128, m1: i128, a2: i128, m2: i128) -> Option<(i128, i128)> {
+    let ExtendedGcd { gcd, x, .. } = m1.extended_gcd(&m2);
+    if (a2 - a1) % gcd != 0 {
+        return None;
+    }
+    let modulus = m1.lcm(&m2);
+    let m = m2 / gcd;
+    let k = ((a2 - a1) / gcd).mod_floor(&m) * x.mod_floor(&m) % m;
+    Some(((a1 + m1 * k).mod_floor(&modulus), modulus))
+}
+
+#[aoc(day8, part2)]
+fn part2(network: &Network) -> Result<usize, WalkError> {
+    let is_end = network.get_matching(&get_part2_goal());
+
+    // Find all nodes whose ids end with "A", and analyze the walk from each of them.
+    let cycles: Vec<Cycle> = network
+        .find_nodes(&get_part2_start())?
         .into_iter()
-        .reduce(|acc, l| acc.lcm(&l))
-        .unwrap()
+        .map(|node| Cycle::find(network, node, |n| is_end[n as usize]))
+        .collect();
+
+    solve_cycles(&cycles)
+}
+
+fn get_part2_start() -> NodeMatcher {
+    NodeMatcher::Suffix("A".to_string())
+}
+
+fn get_part2_goal() -> NodeMatcher {
+    NodeMatcher::Suffix("Z".to_string())
+}
+
+/// Exports the network with the path taken by each ghost to its first end node to a Graphviz DOT
+/// file, if the `DAY8_DOT_DIR` environment variable is set to the directory to write it to.
+#[aoc(day8, part2, Graphviz)]
+fn part2_graphviz(network: &Network) -> Result<usize, Box<dyn Error>> {
+    if let Ok(dir) = env::var("DAY8_DOT_DIR") {
+        let paths = network
+            .find_nodes(&get_part2_start())?
+            .into_iter()
+            .map(|node| network.walk(node, &get_part2_goal(), None))
+            .collect::<Result<Vec<_>, _>>()?;
+        export_dot(network, &paths, &Path::new(&dir).join("day8_part2.dot"))?;
+    }
+    Ok(part2(network)?)
 }
 
 #[cfg(test)]
@@ -102,162 +499,229 @@ mod tests {
 
     #[test]
     fn test_parse_input_part1() {
-        let mut nodes = HashMap::new();
-        nodes.insert(
-            "AAA".to_string(),
-            Node {
-                id: "AAA".to_string(),
-                children: ["BBB".to_string(), "CCC".to_string()],
-            },
-        );
-        nodes.insert(
-            "BBB".to_string(),
-            Node {
-                id: "BBB".to_string(),
-                children: ["DDD".to_string(), "EEE".to_string()],
-            },
-        );
-        nodes.insert(
-            "CCC".to_string(),
-            Node {
-                id: "CCC".to_string(),
-                children: ["ZZZ".to_string(), "GGG".to_string()],
-            },
-        );
-        nodes.insert(
-            "DDD".to_string(),
-            Node {
-                id: "DDD".to_string(),
-                children: ["DDD".to_string(), "DDD".to_string()],
-            },
-        );
-        nodes.insert(
-            "EEE".to_string(),
-            Node {
-                id: "EEE".to_string(),
-                children: ["EEE".to_string(), "EEE".to_string()],
-            },
-        );
-        nodes.insert(
-            "GGG".to_string(),
-            Node {
-                id: "GGG".to_string(),
-                children: ["GGG".to_string(), "GGG".to_string()],
-            },
-        );
-        nodes.insert(
-            "ZZZ".to_string(),
-            Node {
-                id: "ZZZ".to_string(),
-                children: ["ZZZ".to_string(), "ZZZ".to_string()],
-            },
-        );
-        let expected = ("RL".to_string(), nodes);
-        assert_eq!(expected, parse_input_part1(get_test_input_part1_example1()));
-
-        let mut nodes = HashMap::new();
-        nodes.insert(
-            "AAA".to_string(),
-            Node {
-                id: "AAA".to_string(),
-                children: ["BBB".to_string(), "BBB".to_string()],
+        let nodes = HashMap::from([
+            ("AAA", ["BBB", "CCC"]),
+            ("BBB", ["DDD", "EEE"]),
+            ("CCC", ["ZZZ", "GGG"]),
+            ("DDD", ["DDD", "DDD"]),
+            ("EEE", ["EEE", "EEE"]),
+            ("GGG", ["GGG", "GGG"]),
+            ("ZZZ", ["ZZZ", "ZZZ"]),
+        ]);
+        let network = parse_input_part1(get_test_input_part1_example1()).unwrap();
+        assert_eq!(vec![1, 0], network.directions);
+        assert_eq!(nodes, get_nodes(&network));
+
+        let nodes = HashMap::from([
+            ("AAA", ["BBB", "BBB"]),
+            ("BBB", ["AAA", "ZZZ"]),
+            ("ZZZ", ["ZZZ", "ZZZ"]),
+        ]);
+        let network = parse_input_part1(get_test_input_part1_example2()).unwrap();
+        assert_eq!(vec![0, 0, 1], network.directions);
+        assert_eq!(nodes, get_nodes(&network));
+    }
+
+    #[test]
+    fn test_parse_input_part2() {
+        let nodes = HashMap::from([
+            ("11A", ["11B", "XXX"]),
+            ("11B", ["XXX", "11Z"]),
+            ("11Z", ["11B", "XXX"]),
+            ("22A", ["22B", "XXX"]),
+            ("22B", ["22C", "22C"]),
+            ("22C", ["22Z", "22Z"]),
+            ("22Z", ["22B", "22B"]),
+            ("XXX", ["XXX", "XXX"]),
+        ]);
+        let network = parse_input_part2(get_test_input_part2()).unwrap();
+        assert_eq!(vec![0, 1], network.directions);
+        assert_eq!(nodes, get_nodes(&network));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let network = parse_input_part2(indoc! {"
+            L
+
+            11A = (11B, XXX)
+            11B = (11Z, XXX)
+            11Z = (11B, XXX)
+            22A = (22Z, XXX)
+            22B = (22C, XXX)
+            22C = (22Z, XXX)
+            22Z = (22B, XXX)
+            33A = (33Z, XXX)
+            33B = (33Z, XXX)
+            33Z = (33B, XXX)
+            XXX = (XXX, XXX)
+        "})
+        .unwrap();
+        let cycle = |id: &str| {
+            Cycle::find(&network, network.get_index(id).unwrap(), |n| {
+                network.get_id(n).ends_with('Z')
+            })
+        };
+
+        assert_eq!(
+            Cycle {
+                prefix_length: 1,
+                length: 2,
+                end_offsets: vec![2],
             },
+            cycle("11A")
         );
-        nodes.insert(
-            "BBB".to_string(),
-            Node {
-                id: "BBB".to_string(),
-                children: ["AAA".to_string(), "ZZZ".to_string()],
+        assert_eq!(
+            Cycle {
+                prefix_length: 1,
+                length: 3,
+                end_offsets: vec![1],
             },
+            cycle("22A")
         );
-        nodes.insert(
-            "ZZZ".to_string(),
-            Node {
-                id: "ZZZ".to_string(),
-                children: ["ZZZ".to_string(), "ZZZ".to_string()],
-            },
+
+        // The first ghost is on an end node after an even number of steps, the second after 1, 4,
+        // 7, ... steps. Taking the lowest common multiple of the first hits would give 2.
+        assert_eq!(Ok(4), solve_cycles(&[cycle("11A"), cycle("22A")]));
+
+        // The third ghost is on an end node after an odd number of steps, so it never meets the
+        // first ghost.
+        assert_eq!(
+            Err(WalkError::NoCommonGoal),
+            solve_cycles(&[cycle("11A"), cycle("33A")])
         );
-        let expected = ("LLR".to_string(), nodes);
-        assert_eq!(expected, parse_input_part1(get_test_input_part1_example2()));
+
+        // An end node that is only reached in the prefix.
+        let prefix_only = Cycle {
+            prefix_length: 5,
+            length: 3,
+            end_offsets: vec![4],
+        };
+        assert_eq!(Ok(4), solve_cycles(&[cycle("22A"), prefix_only]));
     }
 
     #[test]
-    fn test_parse_input_part2() {
-        let mut nodes = HashMap::new();
-        nodes.insert(
-            "11A".to_string(),
-            Node {
-                id: "11A".to_string(),
-                children: ["11B".to_string(), "XXX".to_string()],
-            },
-        );
-        nodes.insert(
-            "11B".to_string(),
-            Node {
-                id: "11B".to_string(),
-                children: ["XXX".to_string(), "11Z".to_string()],
-            },
+    fn test_display() {
+        // Nodes are output in the order in which they are defined, not in the order in which they
+        // are first referenced.
+        let network = parse_input_part1(get_test_input_part1_example1()).unwrap();
+        assert_eq!(get_test_input_part1_example1(), network.to_string());
+        let network = parse_input_part1(get_test_input_part1_example2()).unwrap();
+        assert_eq!(get_test_input_part1_example2(), network.to_string());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let tests = [
+            ("", "The input is empty."),
+            (
+                "\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\n",
+                "There are no directions.",
+            ),
+            ("LRX\n\nAAA = (AAA, AAA)\n", "Invalid direction 'X'."),
+            ("LR\n\nAAA = BBB\n", "Invalid node 'AAA = BBB'."),
+            (
+                "LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)\n",
+                "Node BBB is not defined.",
+            ),
+            (
+                "LR\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n",
+                "Node AAA is defined more than once.",
+            ),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(Err(expected.to_string()), parse_input_part1(input));
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let network = parse_input_part1(get_test_input_part1_example1()).unwrap();
+        let index = |id: &str| network.get_index(id).unwrap();
+        let id = |id: &str| NodeMatcher::Id(id.to_string());
+        let suffix = |suffix: &str| NodeMatcher::Suffix(suffix.to_string());
+        let regex = |re: &str| NodeMatcher::Regex(Regex::new(re).unwrap());
+
+        assert_eq!(
+            Ok(vec![index("AAA"), index("CCC"), index("ZZZ")]),
+            network.walk(index("AAA"), &id("ZZZ"), None)
         );
-        nodes.insert(
-            "11Z".to_string(),
-            Node {
-                id: "11Z".to_string(),
-                children: ["11B".to_string(), "XXX".to_string()],
-            },
+        assert_eq!(
+            Ok(vec![index("AAA"), index("CCC")]),
+            network.walk(index("AAA"), &regex("^[CG]+$"), None)
         );
-        nodes.insert(
-            "22A".to_string(),
-            Node {
-                id: "22A".to_string(),
-                children: ["22B".to_string(), "XXX".to_string()],
-            },
+        assert_eq!(
+            Ok(vec![index("AAA"), index("CCC"), index("ZZZ")]),
+            network.walk(index("AAA"), &id("ZZZ"), Some(2))
         );
-        nodes.insert(
-            "22B".to_string(),
-            Node {
-                id: "22B".to_string(),
-                children: ["22C".to_string(), "22C".to_string()],
-            },
+        assert_eq!(
+            Err(WalkError::StepLimitReached("AAA".to_string(), 1)),
+            network.walk(index("AAA"), &id("ZZZ"), Some(1))
         );
-        nodes.insert(
-            "22C".to_string(),
-            Node {
-                id: "22C".to_string(),
-                children: ["22Z".to_string(), "22Z".to_string()],
-            },
+        assert_eq!(
+            Err(WalkError::GoalUnreachable("AAA".to_string())),
+            network.walk(index("AAA"), &id("EEE"), None)
         );
-        nodes.insert(
-            "22Z".to_string(),
-            Node {
-                id: "22Z".to_string(),
-                children: ["22B".to_string(), "22B".to_string()],
-            },
+
+        assert_eq!(Ok(vec![index("ZZZ")]), network.find_nodes(&suffix("Z")));
+        assert_eq!(
+            Err(WalkError::NoStartNodes("id XXX".to_string())),
+            network.find_nodes(&id("XXX"))
         );
-        nodes.insert(
-            "XXX".to_string(),
-            Node {
-                id: "XXX".to_string(),
-                children: ["XXX".to_string(), "XXX".to_string()],
-            },
+        let network = parse_input_part1(get_test_input_part2()).unwrap();
+        assert_eq!(
+            Err(WalkError::NoStartNodes("id AAA".to_string())),
+            part1(&network)
         );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let network = parse_input_part1(get_test_input_part1_example2()).unwrap();
+        let expected = indoc! {r#"
+            digraph network {
+                "AAA" [style=filled, fillcolor=palegreen];
+                "AAA" -> "BBB" [label="LR", color=red, penwidth=2];
+                "BBB";
+                "BBB" -> "AAA" [label="L", color=red, penwidth=2];
+                "BBB" -> "ZZZ" [label="R", color=red, penwidth=2];
+                "ZZZ" [style=filled, fillcolor=lightpink];
+                "ZZZ" -> "ZZZ" [label="LR"];
+            }
+        "#};
         assert_eq!(
-            ("LR".to_string(), nodes),
-            parse_input_part2(get_test_input_part2())
+            expected,
+            network.to_dot(&[get_part1_path(&network).unwrap()])
         );
     }
 
     #[test]
     fn part1_example() {
-        let input = parse_input_part1(get_test_input_part1_example1());
-        assert_eq!(2, part1(&input));
-        let input = parse_input_part1(get_test_input_part1_example2());
-        assert_eq!(6, part1(&input));
+        let input = parse_input_part1(get_test_input_part1_example1()).unwrap();
+        assert_eq!(Ok(2), part1(&input));
+        let input = parse_input_part1(get_test_input_part1_example2()).unwrap();
+        assert_eq!(Ok(6), part1(&input));
     }
 
     #[test]
     fn part2_example() {
-        let input = parse_input_part2(get_test_input_part2());
-        assert_eq!(6, part2(&input));
+        let input = parse_input_part2(get_test_input_part2()).unwrap();
+        assert_eq!(Ok(6), part2(&input));
+    }
+
+    /// Returns the children of each node in the network, by id.
+    fn get_nodes(network: &Network) -> HashMap<&str, [&str; 2]> {
+        network
+            .children
+            .iter()
+            .enumerate()
+            .map(|(index, [left, right])| {
+                (
+                    network.get_id(index as u32),
+                    [network.get_id(*left), network.get_id(*right)],
+                )
+            })
+            .collect()
     }
 
     fn get_test_input_part1_example1<'a>() -> &'a str {
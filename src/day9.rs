@@ -1,92 +1,259 @@
-use crate::day9;
 use aoc_runner_derive::{aoc, aoc_generator};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
 
 #[derive(Debug, Clone, PartialEq)]
 struct HistoricValues {
     values: Vec<isize>,
+    /// The number of the line the values were read from, starting from 1.
+    line: usize,
 }
 
-impl<T> From<T> for HistoricValues
-where
-    T: Into<String>,
-{
-    fn from(line: T) -> Self {
-        let line = line.into();
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    /// The token on the given line is not a valid value.
+    InvalidValue { line: usize, token: String },
+    /// The given line does not contain any values.
+    NoValues { line: usize },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidValue { line, token } => {
+                write!(f, "Invalid value '{}' on line {}.", token, line)
+            }
+            ParseError::NoValues { line } => write!(f, "No values on line {}.", line),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a line of values separated by any amount of whitespace, given as a tuple of the line
+/// number and the line.
+impl TryFrom<(usize, &str)> for HistoricValues {
+    type Error = ParseError;
+
+    fn try_from((line_number, line): (usize, &str)) -> Result<Self, Self::Error> {
         let values = line
-            .split(' ')
-            .map(|v| v.trim().parse().unwrap())
-            .collect::<Vec<_>>();
-        HistoricValues { values }
+            .split_whitespace()
+            .map(|token| {
+                token.parse().map_err(|_| ParseError::InvalidValue {
+                    line: line_number,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.is_empty() {
+            return Err(ParseError::NoValues { line: line_number });
+        }
+        Ok(HistoricValues {
+            values,
+            line: line_number,
+        })
     }
 }
 
 impl HistoricValues {
-    fn get_sequences(&self) -> Vec<Vec<isize>> {
+    /// Returns the rows of the difference triangle, or `None` if a difference overflows.
+    fn get_sequences(&self) -> Option<Vec<Vec<isize>>> {
         let mut sequences = vec![];
         sequences.push(self.values.clone());
-        let mut sequence = HistoricValues::get_next_sequence(&self.values);
+        let mut sequence = HistoricValues::get_next_sequence(&self.values)?;
         loop {
             sequences.push(sequence.clone());
             if sequence.iter().all(|v| *v == 0) {
                 break;
             }
-            sequence = day9::HistoricValues::get_next_sequence(&sequence);
+            sequence = HistoricValues::get_next_sequence(&sequence)?;
         }
 
-        sequences
+        Some(sequences)
+    }
+
+    fn get_next_sequence(sequence: &[isize]) -> Option<Vec<isize>> {
+        // Get the difference between each pair of consecutive values in the sequence.
+        sequence
+            .windows(2)
+            .map(|pair| pair[1].checked_sub(pair[0]))
+            .collect()
+    }
+
+    /// Returns the first value of each row in the difference triangle, up to the last row that is
+    /// not all zeros. These are the coefficients of the Newton forward difference formula. Only a
+    /// single row is kept in memory. Returns `None` if a difference overflows.
+    fn get_leading_differences(&self) -> Option<Vec<isize>> {
+        let mut row = self.values.clone();
+        let mut leading_differences = vec![];
+        while !row.iter().all(|v| *v == 0) {
+            leading_differences.push(row[0]);
+            for i in 0..row.len() - 1 {
+                row[i] = row[i + 1].checked_sub(row[i])?;
+            }
+            row.pop();
+        }
+        Some(leading_differences)
+    }
+
+    /// Returns the degree of the lowest degree polynomial that generates the values. If the
+    /// values don't reduce to zeros, this is one less than the number of values.
+    fn get_degree(&self) -> Option<usize> {
+        Some(self.get_leading_differences()?.len().saturating_sub(1))
     }
 
-    fn get_next_sequence(sequence: &[isize]) -> Vec<isize> {
-        let mut next_sequence = vec![];
-        // Loop over the values in the sequence, and get the difference between the current and next
-        // values.
-        let len = sequence.len();
-        for i in 0..len - 1 {
-            let diff = sequence[i + 1] - sequence[i];
-            next_sequence.push(diff);
+    /// Returns the value at the given index, where the first value has index 0. The index can be
+    /// negative or lie far beyond the known values. Returns `None` on overflow.
+    fn extrapolate(&self, index: isize) -> Option<isize> {
+        // Newton's forward difference formula: f(k) = sum of C(k, j) * Δ^j f(0). The binomial
+        // coefficient is generalized to negative k, and is updated incrementally using
+        // C(k, j + 1) = C(k, j) * (k - j) / (j + 1), which is always an exact division.
+        let mut binomial: i128 = 1;
+        let mut value: i128 = 0;
+        for (j, difference) in self.get_leading_differences()?.into_iter().enumerate() {
+            if j > 0 {
+                binomial = binomial.checked_mul(index as i128 - j as i128 + 1)? / j as i128;
+            }
+            value = value.checked_add(binomial.checked_mul(difference as i128)?)?;
         }
+        isize::try_from(value).ok()
+    }
+
+    /// Returns the value following the last known value.
+    fn extrapolate_forward(&self) -> Option<isize> {
+        self.extrapolate(self.values.len() as isize)
+    }
+
+    /// Returns the value preceding the first known value.
+    fn extrapolate_backward(&self) -> Option<isize> {
+        self.extrapolate(-1)
+    }
 
-        next_sequence
+    /// Returns the difference triangle as it is drawn in the puzzle description, or `None` if a
+    /// difference overflows.
+    fn get_pyramid(&self) -> Option<String> {
+        let mut sequences = self.get_sequences()?;
+        sequences.retain(|sequence| !sequence.is_empty());
+        let max_len = sequences
+            .iter()
+            .flatten()
+            .map(|v| v.to_string().len())
+            .max()
+            .unwrap_or(0);
+        // Use an even cell width so every row can be shifted by half a cell.
+        let width = (max_len + 2) / 2 * 2;
+        let rows: Vec<String> = sequences
+            .iter()
+            .enumerate()
+            .map(|(i, sequence)| {
+                let indent = " ".repeat(i * width / 2);
+                let row: String = sequence.iter().map(|v| format!("{:>width$}", v)).collect();
+                indent + &row
+            })
+            .collect();
+        // Remove the padding that all rows have in common.
+        let padding = rows
+            .iter()
+            .map(|row| row.len() - row.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        Some(
+            rows.iter()
+                .map(|row| format!("{}\n", &row[padding..]))
+                .collect(),
+        )
     }
 }
-#[aoc_generator(day9, part1)]
-fn parse_input_part1(input: &str) -> Vec<HistoricValues> {
-    input.lines().map(|line| line.into()).collect()
+
+#[aoc_generator(day9)]
+fn parse_input(input: &str) -> Result<Vec<HistoricValues>, ParseError> {
+    // Skip empty lines, but keep counting them so errors report the right line number.
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| HistoricValues::try_from((i + 1, line)))
+        .collect()
 }
 
-#[aoc_generator(day9, part2)]
-fn parse_input_part2(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+/// Returns the sum of the values extrapolated from each history, or an error naming the line of
+/// the history at which the calculation overflows.
+fn get_extrapolated_sum(
+    input: &[HistoricValues],
+    extrapolate: fn(&HistoricValues) -> Option<isize>,
+) -> Result<isize, String> {
+    input.iter().try_fold(0isize, |sum, history| {
+        let value = extrapolate(history)
+            .ok_or(format!("The history on line {} overflows.", history.line))?;
+        sum.checked_add(value).ok_or(format!(
+            "The sum overflows when adding the history on line {}.",
+            history.line
+        ))
+    })
 }
 
 #[aoc(day9, part1)]
-fn part1(input: &[HistoricValues]) -> isize {
-    input
+fn part1(input: &[HistoricValues]) -> Result<isize, String> {
+    get_extrapolated_sum(input, HistoricValues::extrapolate_forward)
+}
+
+/// Outputs the answer followed by the number of histories per polynomial degree.
+#[aoc(day9, part1, Degrees)]
+fn part1_degrees(input: &[HistoricValues]) -> String {
+    let mut degrees = BTreeMap::new();
+    for history in input {
+        *degrees.entry(history.get_degree()).or_insert(0) += 1;
+    }
+    let breakdown: String = degrees
         .iter()
-        .flat_map(|v| {
-            v.get_sequences()
-                .into_iter()
-                .map(|seq| *seq.last().unwrap())
+        .map(|(degree, count)| match degree {
+            Some(degree) => format!("Degree {}: {} histories\n", degree, count),
+            None => format!("Overflow: {} histories\n", count),
         })
-        .sum()
+        .collect();
+    // Report an overflow instead of the answer, so the breakdown can help to find the cause.
+    let answer = part1(input).map_or_else(|e| e, |answer| answer.to_string());
+    format!("{}\n{}", answer, breakdown)
 }
 
 #[aoc(day9, part2)]
-fn part2(input: &[String]) -> isize {
-    // Loop through the values backwards.
-    input
-        .iter()
-        .rev()
-        .map(|h| {
-            let sequences = HistoricValues::from(h.clone()).get_sequences();
-            sequences
-                .iter()
-                .map(|s| s[0])
-                .rev()
-                .reduce(|acc, v| v - acc)
-                .unwrap()
-        })
-        .sum()
+fn part2(input: &[HistoricValues]) -> Result<isize, String> {
+    get_extrapolated_sum(input, HistoricValues::extrapolate_backward)
+}
+
+/// Returns the history on the given input line, or the first history if no line is given. Blank
+/// lines are counted, even though they don't contain a history.
+fn find_history(input: &[HistoricValues], line: Option<usize>) -> Result<&HistoricValues, String> {
+    match line {
+        Some(line) => input
+            .iter()
+            .find(|history| history.line == line)
+            .ok_or(format!("There is no history on line {}.", line)),
+        None => input.first().ok_or("There are no histories.".to_string()),
+    }
+}
+
+/// Outputs the answer followed by the difference pyramid of the history on the input line given in
+/// `DAY9_PYRAMID_LINE`, starting from 1. Defaults to the first history.
+#[aoc(day9, part2, Pyramid)]
+fn part2_pyramid(input: &[HistoricValues]) -> Result<String, String> {
+    let line = match env::var("DAY9_PYRAMID_LINE") {
+        Ok(line) => Some(line.parse::<usize>().map_err(|e| e.to_string())?),
+        Err(_) => None,
+    };
+    let history = find_history(input, line)?;
+    let pyramid = history.get_pyramid().ok_or(format!(
+        "The differences of the history on line {} overflow.",
+        history.line
+    ))?;
+    Ok(format!(
+        "{}\n{}\nBackward: {:?}\nForward: {:?}\n",
+        part2(input)?,
+        pyramid,
+        history.extrapolate_backward(),
+        history.extrapolate_forward()
+    ))
 }
 
 #[cfg(test)]
@@ -95,37 +262,225 @@ mod tests {
     use indoc::indoc;
 
     #[test]
-    fn test_parse_input_part1() {
+    fn test_parse_input() {
         let expected = vec![
             HistoricValues {
                 values: vec![0, 3, 6, 9, 12, 15],
+                line: 1,
             },
             HistoricValues {
                 values: vec![1, 3, 6, 10, 15, 21],
+                line: 2,
             },
             HistoricValues {
                 values: vec![10, 13, 16, 21, 30, 45],
+                line: 3,
             },
         ];
 
-        assert_eq!(expected, parse_input_part1(get_test_input_part1()));
+        assert_eq!(expected, parse_input(get_test_input_part1()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_input_whitespace() {
+        let input = "\n0  3\t6 9 12 15  \n\n 1 3 6 10 15 21\n\n\n10 13 16 21 30 45\n\n";
+        let histories = parse_input(input).unwrap();
+        let values = |histories: &[HistoricValues]| -> Vec<Vec<isize>> {
+            histories.iter().map(|h| h.values.clone()).collect()
+        };
+        let expected = parse_input(get_test_input_part1()).unwrap();
+        assert_eq!(values(&expected), values(&histories));
+        // The histories keep the number of the line they are on.
+        let lines: Vec<usize> = histories.iter().map(|h| h.line).collect();
+        assert_eq!(vec![2, 4, 7], lines);
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let input = "1 2 3\n\n4 5 six 7\n";
+        assert_eq!(
+            Err(ParseError::InvalidValue {
+                line: 3,
+                token: "six".to_string()
+            }),
+            parse_input(input)
+        );
+        assert_eq!(
+            "Invalid value 'six' on line 3.",
+            parse_input(input).unwrap_err().to_string()
+        );
+        assert_eq!(
+            Err(ParseError::InvalidValue {
+                line: 2,
+                token: "99999999999999999999".to_string()
+            }),
+            parse_input("1\n99999999999999999999")
+        );
+        assert_eq!(
+            Err(ParseError::NoValues { line: 4 }),
+            HistoricValues::try_from((4, " \t "))
+        );
     }
 
     #[test]
-    fn test_parse_input_part2() {
-        test_parse_input_part1();
+    fn test_single_value() {
+        let single = history("42");
+        assert_eq!(Some(vec![vec![42], vec![]]), single.get_sequences());
+        assert_eq!(Some(0), single.get_degree());
+        assert_eq!(Some(42), single.extrapolate_forward());
+        assert_eq!(Some(42), single.extrapolate_backward());
+        assert_eq!(Some("42\n".to_string()), single.get_pyramid());
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let histories = parse_input(get_test_input_part1()).unwrap();
+        let [linear, triangular, cubic] = [&histories[0], &histories[1], &histories[2]];
+
+        assert_eq!(Some(vec![0, 3]), linear.get_leading_differences());
+        assert_eq!(Some(vec![1, 2, 1]), triangular.get_leading_differences());
+        assert_eq!(Some(vec![10, 3, 0, 2]), cubic.get_leading_differences());
+        assert_eq!(Some(1), linear.get_degree());
+        assert_eq!(Some(2), triangular.get_degree());
+        assert_eq!(Some(3), cubic.get_degree());
+
+        // The known values are reproduced exactly.
+        for history in &histories {
+            for (i, value) in history.values.iter().enumerate() {
+                assert_eq!(Some(*value), history.extrapolate(i as isize));
+            }
+        }
+
+        assert_eq!(Some(18), linear.extrapolate(6));
+        assert_eq!(Some(28), triangular.extrapolate(6));
+        assert_eq!(Some(68), cubic.extrapolate(6));
+        assert_eq!(Some(-3), linear.extrapolate(-1));
+        assert_eq!(Some(0), triangular.extrapolate(-1));
+        assert_eq!(Some(5), cubic.extrapolate(-1));
+        assert_eq!(Some(3_000_000), linear.extrapolate(1_000_000));
+        assert_eq!(Some(501_501), triangular.extrapolate(1000));
+        assert_eq!(Some(-3_000_000), linear.extrapolate(-1_000_000));
+
+        // Constant and all zero values.
+        let constant = history("7 7 7");
+        assert_eq!(Some(0), constant.get_degree());
+        assert_eq!(Some(7), constant.extrapolate(-100));
+        let zeros = history("0 0");
+        assert_eq!(Some(0), zeros.get_degree());
+        assert_eq!(Some(0), zeros.extrapolate(5));
+
+        // Values that don't reduce to zeros are treated as a polynomial of maximal degree.
+        let square = history("0 1 4");
+        assert_eq!(Some(2), square.get_degree());
+        assert_eq!(Some(9), square.extrapolate(3));
+
+        // Overflows are detected.
+        let large = history(&format!("0 {}", isize::MAX));
+        assert_eq!(Some(isize::MAX), large.extrapolate(1));
+        assert_eq!(None, large.extrapolate(2));
+        let huge_difference = history(&format!("{} {}", isize::MIN, isize::MAX));
+        assert_eq!(None, huge_difference.extrapolate(0));
+    }
+
+    #[test]
+    fn test_extrapolate_forward_and_backward() {
+        let histories = parse_input(get_test_input_part1()).unwrap();
+        let forward: Vec<_> = histories.iter().map(|h| h.extrapolate_forward()).collect();
+        let backward: Vec<_> = histories.iter().map(|h| h.extrapolate_backward()).collect();
+        assert_eq!(vec![Some(18), Some(28), Some(68)], forward);
+        assert_eq!(vec![Some(-3), Some(0), Some(5)], backward);
+    }
+
+    #[test]
+    fn test_get_pyramid() {
+        let histories = parse_input(get_test_input_part1()).unwrap();
+        let expected = indoc! {"
+            0   3   6   9  12  15
+              3   3   3   3   3
+                0   0   0   0
+        "};
+        assert_eq!(expected, histories[0].get_pyramid().unwrap());
+        let expected = indoc! {"
+            10  13  16  21  30  45
+               3   3   5   9  15
+                 0   2   4   6
+                   2   2   2
+                     0   0
+        "};
+        assert_eq!(expected, histories[2].get_pyramid().unwrap());
+        let expected = indoc! {"
+            -5 -10
+              -5
+        "};
+        assert_eq!(expected, history("-5 -10").get_pyramid().unwrap());
+
+        // Differences that overflow are detected.
+        let huge_difference = history(&format!("{} {}", isize::MIN, isize::MAX));
+        assert_eq!(None, huge_difference.get_sequences());
+        assert_eq!(None, huge_difference.get_pyramid());
+    }
+
+    #[test]
+    fn test_find_history() {
+        let input = parse_input("1 2 3\n\n4 5 6\n").unwrap();
+        assert_eq!(Ok(&input[0]), find_history(&input, None));
+        assert_eq!(Ok(&input[0]), find_history(&input, Some(1)));
+        assert_eq!(Ok(&input[1]), find_history(&input, Some(3)));
+        assert_eq!(
+            Err("There is no history on line 2.".to_string()),
+            find_history(&input, Some(2))
+        );
+        assert_eq!(
+            Err("There are no histories.".to_string()),
+            find_history(&[], None)
+        );
     }
 
     #[test]
     fn part1_example() {
-        let input = parse_input_part1(get_test_input_part1());
-        assert_eq!(114, part1(&input));
+        let input = parse_input(get_test_input_part1()).unwrap();
+        assert_eq!(Ok(114), part1(&input));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // The extrapolated values overflow.
+        let input = parse_input(&format!("1 2 3\n\n0 {}\n", isize::MAX)).unwrap();
+        let expected = Err("The history on line 3 overflows.".to_string());
+        assert_eq!(expected, part1(&input));
+        // Going backward stays in range.
+        assert_eq!(Ok(-isize::MAX), part2(&input));
+
+        // The extrapolated values fit, but their sum overflows.
+        let input = parse_input(&format!("{0} {0}\n{0} {0}\n", isize::MAX)).unwrap();
+        let expected = "The sum overflows when adding the history on line 2.";
+        assert_eq!(Err(expected.to_string()), part1(&input));
+        assert_eq!(
+            format!("{}\nDegree 0: 2 histories\n", expected),
+            part1_degrees(&input)
+        );
+    }
+
+    #[test]
+    fn part1_degrees_example() {
+        let input = parse_input(get_test_input_part1()).unwrap();
+        let expected = indoc! {"
+            114
+            Degree 1: 1 histories
+            Degree 2: 1 histories
+            Degree 3: 1 histories
+        "};
+        assert_eq!(expected, part1_degrees(&input));
     }
 
     #[test]
     fn part2_example() {
-        let input = parse_input_part2(get_test_input_part2());
-        assert_eq!(2, part2(&input));
+        let input = parse_input(get_test_input_part2()).unwrap();
+        assert_eq!(Ok(2), part2(&input));
+    }
+
+    fn history(line: &str) -> HistoricValues {
+        HistoricValues::try_from((1, line)).unwrap()
     }
 
     fn get_test_input_part1<'a>() -> &'a str {
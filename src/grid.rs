@@ -0,0 +1,226 @@
+use std::ops::{Index, IndexMut};
+
+/// The offsets of the four orthogonal neighbours, clockwise starting from north.
+const NEIGHBORS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The offsets of the eight orthogonal and diagonal neighbours, clockwise starting from north.
+const NEIGHBORS_8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Coordinate {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// A dense two dimensional grid, stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from its rows. All rows should have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "All rows should have the same length."
+        );
+        Grid {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        }
+    }
+
+    /// Parses a grid from text, converting each character into a cell.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> T) -> Self {
+        let rows = input
+            .lines()
+            .map(|line| line.chars().map(&mut f).collect())
+            .collect();
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the cell at the given coordinate, or `None` if it lies outside of the grid.
+    pub fn get(&self, c: Coordinate) -> Option<&T> {
+        match c.x < self.width && c.y < self.height {
+            true => Some(&self.cells[c.y * self.width + c.x]),
+            false => None,
+        }
+    }
+
+    /// Returns the coordinate at the given offset, or `None` if it lies outside of the grid.
+    pub fn neighbor(&self, c: Coordinate, (dx, dy): (isize, isize)) -> Option<Coordinate> {
+        let x = c.x.checked_add_signed(dx)?;
+        let y = c.y.checked_add_signed(dy)?;
+        match x < self.width && y < self.height {
+            true => Some(Coordinate { x, y }),
+            false => None,
+        }
+    }
+
+    /// Returns the orthogonal neighbours of the given coordinate that lie inside the grid.
+    pub fn neighbors4(&self, c: Coordinate) -> impl Iterator<Item = Coordinate> + '_ {
+        NEIGHBORS_4
+            .iter()
+            .filter_map(move |offset| self.neighbor(c, *offset))
+    }
+
+    /// Returns the orthogonal and diagonal neighbours of the given coordinate that lie inside the
+    /// grid.
+    pub fn neighbors8(&self, c: Coordinate) -> impl Iterator<Item = Coordinate> + '_ {
+        NEIGHBORS_8
+            .iter()
+            .filter_map(move |offset| self.neighbor(c, *offset))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Returns all cells together with their coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.cells.iter().enumerate().map(|(i, cell)| {
+            let c = Coordinate {
+                x: i % self.width,
+                y: i / self.width,
+            };
+            (c, cell)
+        })
+    }
+
+    /// Renders the grid as text, converting each cell into a character.
+    pub fn render(&self, f: impl Fn(&T) -> char) -> String {
+        self.rows()
+            .flat_map(|row| row.iter().map(&f).chain(std::iter::once('\n')))
+            .collect()
+    }
+}
+
+impl<T> Index<Coordinate> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, c: Coordinate) -> &Self::Output {
+        self.get(c).expect("Coordinate outside of the grid.")
+    }
+}
+
+impl<T> IndexMut<Coordinate> for Grid<T> {
+    fn index_mut(&mut self, c: Coordinate) -> &mut Self::Output {
+        assert!(
+            c.x < self.width && c.y < self.height,
+            "Coordinate outside of the grid."
+        );
+        &mut self.cells[c.y * self.width + c.x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_and_render() {
+        let grid = Grid::parse(get_test_input(), |c| c.to_digit(10).unwrap());
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        assert_eq!(Some(&6), grid.get(Coordinate { x: 2, y: 1 }));
+        assert_eq!(None, grid.get(Coordinate { x: 3, y: 1 }));
+        assert_eq!(None, grid.get(Coordinate { x: 0, y: 2 }));
+        assert_eq!(4, grid[Coordinate { x: 0, y: 1 }]);
+
+        let rendered = grid.render(|d| char::from_digit(*d, 10).unwrap());
+        assert_eq!(get_test_input(), rendered);
+
+        let empty: Grid<char> = Grid::parse("", |c| c);
+        assert_eq!((0, 0), (empty.width(), empty.height()));
+        assert_eq!("", empty.render(|c| *c));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::parse(get_test_input(), |c| c.to_digit(10).unwrap());
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(vec![&[1, 2, 3][..], &[4, 5, 6][..]], rows);
+        let columns: Vec<Vec<_>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(vec![vec![1, 4], vec![2, 5], vec![3, 6]], columns);
+        let cells: Vec<_> = grid.iter().map(|(c, v)| (c.x, c.y, *v)).collect();
+        assert_eq!(
+            vec![
+                (0, 0, 1),
+                (1, 0, 2),
+                (2, 0, 3),
+                (0, 1, 4),
+                (1, 1, 5),
+                (2, 1, 6)
+            ],
+            cells
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let mut grid = Grid::parse(get_test_input(), |c| c.to_digit(10).unwrap());
+        let values = |coordinates: Vec<Coordinate>| -> Vec<u32> {
+            coordinates.into_iter().map(|c| grid[c]).collect()
+        };
+        let corner = Coordinate { x: 0, y: 0 };
+        let middle = Coordinate { x: 1, y: 1 };
+        assert_eq!(vec![2, 4], values(grid.neighbors4(corner).collect()));
+        assert_eq!(vec![2, 5, 4], values(grid.neighbors8(corner).collect()));
+        assert_eq!(vec![2, 6, 4], values(grid.neighbors4(middle).collect()));
+        assert_eq!(
+            vec![2, 3, 6, 4, 1],
+            values(grid.neighbors8(middle).collect())
+        );
+
+        grid[middle] = 0;
+        assert_eq!(
+            "123\n406\n",
+            grid.render(|d| char::from_digit(*d, 10).unwrap())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "All rows should have the same length.")]
+    fn test_ragged_rows() {
+        Grid::parse("12\n3\n", |c| c);
+    }
+
+    fn get_test_input<'a>() -> &'a str {
+        indoc! {"
+            123
+            456
+        "}
+    }
+}
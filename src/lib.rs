@@ -11,5 +11,6 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod grid;
 
 aoc_lib! { year = 2023 }